@@ -0,0 +1,5 @@
+use pebbles_factory_io::FactoryMetadata;
+
+fn main() {
+    gear_wasm_builder::build_with_metadata::<FactoryMetadata>();
+}
\ No newline at end of file
@@ -0,0 +1,52 @@
+#![no_std]
+
+use gmeta::{In, InOut, Metadata};
+use gstd::{prelude::*, ActorId, CodeId};
+use pebbles_game_io::PebblesInit;
+
+#[derive(Debug, Clone, Encode, Decode, TypeInfo)]
+pub struct FactoryInit {
+    /// Code of the pebbles game program deployed for every requester.
+    pub pebbles_code_id: CodeId,
+}
+
+#[derive(Debug, Clone, Encode, Decode, TypeInfo)]
+pub enum FactoryAction {
+    CreateGame(PebblesInit),
+}
+
+#[derive(Debug, Clone, Encode, Decode, TypeInfo)]
+pub enum FactoryEvent {
+    GameCreated { owner: ActorId, program_id: ActorId },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, TypeInfo)]
+pub struct GameInstance {
+    pub owner: ActorId,
+    pub program_id: ActorId,
+    /// Block timestamp of the deployment.
+    pub created_at: u64,
+}
+
+#[derive(Debug, Clone, Encode, Decode, TypeInfo)]
+pub enum FactoryStateQuery {
+    CodeId,
+    Instances(ActorId),
+}
+
+#[derive(Debug, Clone, Encode, Decode, TypeInfo)]
+pub enum FactoryStateReply {
+    CodeId(CodeId),
+    Instances(Vec<GameInstance>),
+}
+
+pub struct FactoryMetadata;
+
+impl Metadata for FactoryMetadata {
+    type Init = In<FactoryInit>;
+    type Handle = InOut<FactoryAction, FactoryEvent>;
+    type State = InOut<FactoryStateQuery, FactoryStateReply>;
+    type Reply = ();
+    type Others = ();
+    type Signal = ();
+}
@@ -0,0 +1,74 @@
+#![no_std]
+
+use core::ptr::addr_of_mut;
+use gstd::{exec, msg, prelude::*, prog::ProgramGenerator, CodeId};
+use pebbles_factory_io::*;
+
+static mut FACTORY: Option<Factory> = None;
+
+fn factory() -> &'static mut Factory {
+    unsafe { (*addr_of_mut!(FACTORY)).as_mut().expect("Factory isn't initialized") }
+}
+
+struct Factory {
+    pebbles_code_id: CodeId,
+    instances: Vec<GameInstance>,
+}
+
+#[no_mangle]
+extern fn init() {
+    let init: FactoryInit = msg::load().expect("Unable to load init message");
+
+    unsafe {
+        FACTORY = Some(Factory {
+            pebbles_code_id: init.pebbles_code_id,
+            instances: Vec::new(),
+        })
+    };
+}
+
+#[no_mangle]
+extern fn handle() {
+    let action: FactoryAction = msg::load().expect("Unable to load action message");
+    let factory = factory();
+
+    match action {
+        FactoryAction::CreateGame(mut init) => {
+            // Reject bad parameters here rather than deploying a program that fails to initialize
+            init.assert_valid();
+
+            // The requester plays the initial game, not the factory that deploys it
+            let owner = msg::source();
+            init.player = Some(owner);
+            let (_, program_id) = ProgramGenerator::create_program(factory.pebbles_code_id, init, 0)
+                .expect("Unable to create pebbles game program");
+            factory.instances.push(GameInstance {
+                owner,
+                program_id,
+                created_at: exec::block_timestamp(),
+            });
+
+            msg::reply(FactoryEvent::GameCreated { owner, program_id }, 0).expect("Unable to send message");
+        }
+    }
+}
+
+/// Answers a FactoryStateQuery using the msg::reply function
+#[no_mangle]
+extern fn state() {
+    let query: FactoryStateQuery = msg::load().expect("Unable to load state query");
+    let factory = factory();
+
+    let reply = match query {
+        FactoryStateQuery::CodeId => FactoryStateReply::CodeId(factory.pebbles_code_id),
+        FactoryStateQuery::Instances(owner) => FactoryStateReply::Instances(
+            factory
+                .instances
+                .iter()
+                .filter(|instance| instance.owner == owner)
+                .cloned()
+                .collect(),
+        ),
+    };
+    msg::reply(reply, 0).expect("Failed to reply with state");
+}
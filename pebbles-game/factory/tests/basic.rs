@@ -0,0 +1,102 @@
+#[cfg(test)]
+mod tests {
+    use gstd::{prelude::*, ActorId};
+    use gtest::{Program, System};
+    use pebbles_factory_io::*;
+    use pebbles_game_io::*;
+
+    // Built into the shared workspace target by the pebbles-game build script, which runs
+    // first since the factory build-depends on pebbles-game
+    const PEBBLES_WASM: &str = "../target/wasm32-unknown-unknown/debug/pebbles_game.opt.wasm";
+    const FACTORY_ID: u64 = 1;
+    const OWNER1: u64 = 10;
+    const OWNER2: u64 = 11;
+
+    fn setup(sys: &System) -> Program<'_> {
+        let pebbles_code_id = sys.submit_code_file(PEBBLES_WASM);
+        let factory = Program::current_with_id(sys, FACTORY_ID);
+        assert!(!factory.send(OWNER1, FactoryInit { pebbles_code_id }).main_failed());
+        factory
+    }
+
+    fn create_game(factory: &Program, owner: u64, pebbles_count: u32) {
+        let init = PebblesInit {
+            difficulty: DifficultyLevel::Easy,
+            pebbles_count,
+            max_pebbles_per_turn: 3,
+            ..Default::default()
+        };
+        assert!(!factory.send(owner, FactoryAction::CreateGame(init)).main_failed());
+    }
+
+    fn instances(factory: &Program, owner: u64) -> Vec<GameInstance> {
+        match factory
+            .read_state(FactoryStateQuery::Instances(owner.into()))
+            .expect("Failed to read state")
+        {
+            FactoryStateReply::Instances(instances) => instances,
+            reply => panic!("Unexpected state reply: {:?}", reply),
+        }
+    }
+
+    #[test]
+    fn test_create_games_per_owner() {
+        let sys = System::new();
+        sys.init_logger();
+        let factory = setup(&sys);
+
+        create_game(&factory, OWNER1, 10);
+        create_game(&factory, OWNER1, 20);
+        create_game(&factory, OWNER2, 30);
+
+        let owner1_games = instances(&factory, OWNER1);
+        let owner2_games = instances(&factory, OWNER2);
+        assert_eq!(owner1_games.len(), 2);
+        assert_eq!(owner2_games.len(), 1);
+        assert!(owner1_games.iter().all(|instance| instance.owner == ActorId::from(OWNER1)));
+
+        // Every instance is a separate pebbles game initialized with its own parameters,
+        // the initial game belongs to the owner who requested it
+        for (instance, pebbles_count) in owner1_games.iter().chain(&owner2_games).zip([10, 20, 30]) {
+            let game = sys
+                .get_program(<[u8; 32]>::from(instance.program_id))
+                .expect("Game program wasn't deployed");
+            match game
+                .read_state(StateQuery::Game(instance.owner))
+                .expect("Failed to read state")
+            {
+                StateReply::Game(Some(state)) => assert_eq!(state.pebbles_count, pebbles_count),
+                reply => panic!("Unexpected state reply: {:?}", reply),
+            }
+        }
+
+        // The owner can play the deployed game right away
+        let game = sys
+            .get_program(<[u8; 32]>::from(owner2_games[0].program_id))
+            .expect("Game program wasn't deployed");
+        assert!(!game.send(OWNER2, PebblesAction::Turn(1)).main_failed());
+        match game
+            .read_state(StateQuery::Game(OWNER2.into()))
+            .expect("Failed to read state")
+        {
+            StateReply::Game(Some(state)) => assert_eq!(state.user_moves, 1),
+            reply => panic!("Unexpected state reply: {:?}", reply),
+        }
+    }
+
+    #[test]
+    fn test_invalid_game_rejected() {
+        let sys = System::new();
+        sys.init_logger();
+        let factory = setup(&sys);
+
+        let init = PebblesInit {
+            difficulty: DifficultyLevel::Easy,
+            pebbles_count: 3,
+            max_pebbles_per_turn: 5,
+            ..Default::default()
+        };
+        assert!(factory.send(OWNER1, FactoryAction::CreateGame(init)).main_failed());
+        assert!(instances(&factory, OWNER1).is_empty());
+    }
+}
@@ -1,7 +1,7 @@
 #![no_std]
 
-use gmeta::{In, InOut, Metadata, Out};
-use gstd::prelude::*;
+use gmeta::{In, InOut, Metadata};
+use gstd::{prelude::*, ActorId};
 
 #[derive(Debug, Default, Clone, Encode, Decode, TypeInfo)]
 pub enum DifficultyLevel {
@@ -15,6 +15,118 @@ pub struct PebblesInit {
     pub difficulty: DifficultyLevel,
     pub pebbles_count: u32,
     pub max_pebbles_per_turn: u32,
+    /// Whoever takes the last pebble loses instead of winning.
+    pub misere: bool,
+    pub reward: Option<RewardConfig>,
+    pub nft_program: Option<ActorId>,
+    /// Player of the initial game, the deployer when not set.
+    pub player: Option<ActorId>,
+}
+
+impl PebblesInit {
+    pub fn assert_valid(&self) {
+        assert!(is_valid_game(self.pebbles_count, self.max_pebbles_per_turn), "Invalid init parameters");
+    }
+}
+
+/// Whether a game with these parameters can be played, shared by init and restart.
+pub fn is_valid_game(pebbles_count: u32, max_pebbles_per_turn: u32) -> bool {
+    pebbles_count > 0 && max_pebbles_per_turn > 0 && max_pebbles_per_turn <= pebbles_count
+}
+
+/// Fungible-token reward paid out to a player who beats the program.
+#[derive(Debug, Default, Clone, Encode, Decode, TypeInfo)]
+pub struct RewardConfig {
+    pub ft_program: ActorId,
+    pub easy_reward: u128,
+    pub hard_reward: u128,
+    /// Paid in full when the player wins in no more moves than optimal play needs, see
+    /// [`GameState::optimal_moves`], and in proportion otherwise.
+    pub speed_bonus: u128,
+    /// Games that optimal play finishes in fewer moves earn nothing, so restarting tiny games
+    /// until the coin flip favours the player doesn't pay.
+    pub min_moves: u32,
+}
+
+impl RewardConfig {
+    /// Reward for beating the program in this game, `None` if the game is too small.
+    pub fn amount(&self, state: &GameState) -> Option<u128> {
+        let optimal = state.optimal_moves();
+        if optimal < self.min_moves {
+            return None;
+        }
+        let base = match state.difficulty {
+            DifficultyLevel::Easy => self.easy_reward,
+            DifficultyLevel::Hard => self.hard_reward,
+        };
+        let bonus = self.speed_bonus * optimal as u128 / state.user_moves.max(optimal) as u128;
+        Some(base.saturating_add(bonus))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, TypeInfo)]
+pub struct Reward {
+    pub player: ActorId,
+    pub amount: u128,
+}
+
+/// Message accepted by the configured fungible-token program.
+#[derive(Debug, Clone, Encode, Decode, TypeInfo)]
+pub enum FTAction {
+    Mint { to: ActorId, amount: u128 },
+}
+
+#[derive(Debug, Clone, Encode, Decode, TypeInfo)]
+pub enum FTEvent {
+    Minted { to: ActorId, amount: u128 },
+}
+
+/// Most actors that can watch a single game at once.
+pub const MAX_SPECTATORS: usize = 16;
+
+/// Consecutive wins needed for [`Achievement::WinStreak`].
+pub const WIN_STREAK: u32 = 10;
+
+/// Moves optimal play needs, see [`GameState::optimal_moves`], before a win counts toward
+/// achievements and the win streak.
+pub const MIN_MILESTONE_MOVES: u32 = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Encode, Decode, TypeInfo)]
+pub enum Achievement {
+    FirstHardWin,
+    WinStreak,
+    MisereWin,
+}
+
+impl Achievement {
+    pub fn metadata(&self) -> AchievementMetadata {
+        let (name, description) = match self {
+            Achievement::FirstHardWin => ("First Hard win", "Beat the program on Hard difficulty"),
+            Achievement::WinStreak => ("Win streak", "Win ten games in a row"),
+            Achievement::MisereWin => ("Misère win", "Win a game where taking the last pebble loses"),
+        };
+        AchievementMetadata {
+            achievement: *self,
+            name: name.into(),
+            description: description.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, TypeInfo)]
+pub struct AchievementMetadata {
+    pub achievement: Achievement,
+    pub name: String,
+    pub description: String,
+}
+
+/// Message accepted by the configured NFT program.
+#[derive(Debug, Clone, Encode, Decode, TypeInfo)]
+pub enum NFTAction {
+    Mint {
+        to: ActorId,
+        token_metadata: AchievementMetadata,
+    },
 }
 
 #[derive(Debug, Clone, Encode, Decode, TypeInfo)]
@@ -25,6 +137,14 @@ pub enum PebblesAction {
         difficulty: DifficultyLevel,
         pebbles_count: u32,
         max_pebbles_per_turn: u32,
+        misere: bool,
+    },
+    RetryRewards,
+    Watch {
+        player: ActorId,
+    },
+    Unwatch {
+        player: ActorId,
     },
 }
 
@@ -47,9 +167,26 @@ impl PartialEq for Player {
 
 #[derive(Debug, Clone, Encode, Decode, TypeInfo)]
 pub enum PebblesEvent {
+    Turn(u32),
     CounterTurn(u32),
     Won(Player),
     InvalidMove,
+    InvalidParameters,
+    RewardFailed(Reward),
+    AchievementFailed(Achievement),
+    RewardsRetried(u32),
+    Watching(ActorId),
+    Unwatched(ActorId),
+    SpectatorLimitReached,
+    GameNotActive,
+    Restarted(GameState),
+}
+
+/// Event pushed to spectators, the game is identified by its player.
+#[derive(Debug, Clone, Encode, Decode, TypeInfo)]
+pub struct SpectatorEvent {
+    pub game: ActorId,
+    pub event: PebblesEvent,
 }
 
 #[derive(Debug, Default, Clone, Encode, Decode, TypeInfo)]
@@ -60,6 +197,66 @@ pub struct GameState {
     pub difficulty: DifficultyLevel,
     pub first_player: Player,
     pub winner: Option<Player>,
+    pub user_moves: u32,
+    pub misere: bool,
+}
+
+impl GameState {
+    /// Moves the winner makes when every round removes `max_pebbles_per_turn + 1` pebbles,
+    /// the pace of optimal play.
+    pub fn optimal_moves(&self) -> u32 {
+        self.pebbles_count.div_ceil(self.max_pebbles_per_turn.saturating_add(1))
+    }
+
+    /// Numbers of pebbles that can be taken on the next turn.
+    pub fn legal_moves(&self) -> Vec<u32> {
+        if self.winner.is_some() {
+            return Vec::new();
+        }
+        (1..=self.max_pebbles_per_turn.min(self.pebbles_remaining)).collect()
+    }
+}
+
+/// Whether the player to move can force a win when up to `k` pebbles may be taken per turn.
+/// In misère play taking the last pebble loses, so the losing positions are shifted by one.
+pub fn is_winning_position(remaining: u32, k: u32, misere: bool) -> bool {
+    let losing = if misere { 1 } else { 0 };
+    remaining % k.saturating_add(1) != losing
+}
+
+#[derive(Debug, Clone, PartialEq, Encode, Decode, TypeInfo)]
+pub struct Move {
+    pub player: Player,
+    pub pebbles: u32,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Encode, Decode, TypeInfo)]
+pub struct PlayerStats {
+    pub games_played: u32,
+    pub wins: u32,
+    pub losses: u32,
+    pub win_streak: u32,
+    pub best_streak: u32,
+}
+
+#[derive(Debug, Clone, Encode, Decode, TypeInfo)]
+pub enum StateQuery {
+    Game(ActorId),
+    LegalMoves(ActorId),
+    IsWinningPosition { remaining: u32, k: u32, misere: bool },
+    History(ActorId),
+    Stats(ActorId),
+    Achievements(ActorId),
+}
+
+#[derive(Debug, Clone, Encode, Decode, TypeInfo)]
+pub enum StateReply {
+    Game(Option<GameState>),
+    LegalMoves(Vec<u32>),
+    IsWinningPosition(bool),
+    History(Vec<Move>),
+    Stats(PlayerStats),
+    Achievements(Vec<Achievement>),
 }
 
 pub struct PebblesMetadata;
@@ -67,7 +264,7 @@ pub struct PebblesMetadata;
 impl Metadata for PebblesMetadata {
     type Init = In<PebblesInit>;
     type Handle = InOut<PebblesAction, PebblesEvent>;
-    type State = Out<GameState>;
+    type State = InOut<StateQuery, StateReply>;
     type Reply = ();
     type Others = ();
     type Signal = ();
@@ -1,121 +1,390 @@
 #![no_std]
 
-use gstd::{exec, msg, prelude::*};
+use gstd::{
+    collections::{BTreeMap, BTreeSet},
+    exec, msg,
+    prelude::*,
+    ActorId, MessageId,
+};
+use core::ptr::addr_of_mut;
 use pebbles_game_io::*;
 
-static mut GAME_STATE: Option<GameState> = None;
+static mut GAMES: Option<BTreeMap<ActorId, Game>> = None;
+static mut REWARDS: Option<Rewards> = None;
+static mut PLAYERS: Option<Players> = None;
+
+fn games() -> &'static mut BTreeMap<ActorId, Game> {
+    unsafe { (*addr_of_mut!(GAMES)).as_mut().expect("Games aren't initialized") }
+}
+
+fn rewards() -> &'static mut Rewards {
+    unsafe { (*addr_of_mut!(REWARDS)).as_mut().expect("Rewards aren't initialized") }
+}
+
+fn players() -> &'static mut Players {
+    unsafe { (*addr_of_mut!(PLAYERS)).as_mut().expect("Players aren't initialized") }
+}
+
+#[derive(Default)]
+struct Game {
+    state: GameState,
+    history: Vec<Move>,
+    spectators: Vec<ActorId>,
+}
+
+impl Game {
+    /// Starts a game on a coin flip, the program makes its opening move right away
+    fn start(difficulty: DifficultyLevel, pebbles_count: u32, max_pebbles_per_turn: u32, misere: bool) -> Self {
+        let first_player = if get_random_u32() % 2 == 0 {
+            Player::User
+        } else {
+            Player::Program
+        };
+        let mut game = Game {
+            state: GameState {
+                pebbles_count,
+                max_pebbles_per_turn,
+                pebbles_remaining: pebbles_count,
+                difficulty,
+                first_player: first_player.clone(),
+                winner: None,
+                user_moves: 0,
+                misere,
+            },
+            ..Default::default()
+        };
+        if first_player == Player::Program {
+            game.program_turn();
+        }
+        game
+    }
+
+    /// Takes pebbles for the program and returns how many
+    fn program_turn(&mut self) -> u32 {
+        let state = &mut self.state;
+        let pebbles = match state.difficulty {
+            DifficultyLevel::Easy => (get_random_u32() % state.max_pebbles_per_turn) + 1,
+            DifficultyLevel::Hard => find_best_move(state.max_pebbles_per_turn, state.pebbles_remaining, state.misere),
+        }
+        .min(state.pebbles_remaining);
+        state.pebbles_remaining -= pebbles;
+        state.winner = check_winner(state, Player::Program);
+        self.history.push(Move {
+            player: Player::Program,
+            pebbles,
+        });
+        pebbles
+    }
+}
+
+#[derive(Default)]
+struct Rewards {
+    config: Option<RewardConfig>,
+    // Mint messages waiting for a reply from the token program
+    pending: BTreeMap<MessageId, Reward>,
+    failed: Vec<Reward>,
+}
+
+impl Rewards {
+    fn mint(&mut self, reward: Reward) {
+        let Some(config) = &self.config else {
+            return;
+        };
+        let action = FTAction::Mint {
+            to: reward.player,
+            amount: reward.amount,
+        };
+        match msg::send(config.ft_program, action, 0) {
+            Ok(message_id) => {
+                self.pending.insert(message_id, reward);
+            }
+            Err(_) => self.failed.push(reward),
+        }
+    }
+
+    fn retry(&mut self) -> u32 {
+        let failed = core::mem::take(&mut self.failed);
+        let retried = failed.len() as u32;
+        for reward in failed {
+            self.mint(reward);
+        }
+        retried
+    }
+}
+
+#[derive(Default)]
+struct Players {
+    nft_program: Option<ActorId>,
+    progress: BTreeMap<ActorId, PlayerProgress>,
+    pending: BTreeMap<MessageId, (ActorId, Achievement)>,
+    failed: Vec<(ActorId, Achievement)>,
+}
+
+#[derive(Default)]
+struct PlayerProgress {
+    stats: PlayerStats,
+    earned: BTreeSet<Achievement>,
+}
+
+impl Players {
+    fn record(&mut self, player: ActorId, state: &GameState) {
+        let progress = self.progress.entry(player).or_default();
+        let stats = &mut progress.stats;
+        stats.games_played += 1;
+        if state.winner != Some(Player::User) {
+            stats.losses += 1;
+            stats.win_streak = 0;
+            return;
+        }
+        stats.wins += 1;
+        // Tiny games are decided by who moves first, they don't count toward milestones
+        if state.optimal_moves() < MIN_MILESTONE_MOVES {
+            return;
+        }
+        stats.win_streak += 1;
+        stats.best_streak = stats.best_streak.max(stats.win_streak);
+
+        let mut reached = Vec::new();
+        if let DifficultyLevel::Hard = state.difficulty {
+            reached.push(Achievement::FirstHardWin);
+        }
+        if progress.stats.win_streak >= WIN_STREAK {
+            reached.push(Achievement::WinStreak);
+        }
+        if state.misere {
+            reached.push(Achievement::MisereWin);
+        }
+
+        // Each achievement is minted only the first time it is earned
+        reached.retain(|achievement| progress.earned.insert(*achievement));
+        for achievement in reached {
+            self.mint(player, achievement);
+        }
+    }
+
+    fn mint(&mut self, player: ActorId, achievement: Achievement) {
+        let Some(nft_program) = self.nft_program else {
+            return;
+        };
+        let action = NFTAction::Mint {
+            to: player,
+            token_metadata: achievement.metadata(),
+        };
+        match msg::send(nft_program, action, 0) {
+            Ok(message_id) => {
+                self.pending.insert(message_id, (player, achievement));
+            }
+            Err(_) => self.failed.push((player, achievement)),
+        }
+    }
+
+    fn retry(&mut self) -> u32 {
+        let failed = core::mem::take(&mut self.failed);
+        let retried = failed.len() as u32;
+        for (player, achievement) in failed {
+            self.mint(player, achievement);
+        }
+        retried
+    }
+}
 
 #[no_mangle]
 extern fn init() {
     let init: PebblesInit = msg::load().expect("Unable to load init message");
 
     // Check for valid input data
-    if init.pebbles_count <= 0 || init.max_pebbles_per_turn <= 0 || init.max_pebbles_per_turn > init.pebbles_count {
-        panic!("Invalid init parameters");
-    }
+    init.assert_valid();
 
-    // Choose the first player
-    let first_player = if get_random_u32() % 2 == 0 {
-        Player::User
-    } else {
-        Player::Program
-    };
+    unsafe {
+        REWARDS = Some(Rewards {
+            config: init.reward,
+            ..Default::default()
+        });
+        PLAYERS = Some(Players {
+            nft_program: init.nft_program,
+            ..Default::default()
+        });
+    }
 
-    // Initialize the game state
-    let mut state = GameState {
-        pebbles_count: init.pebbles_count,
-        max_pebbles_per_turn: init.max_pebbles_per_turn,
-        pebbles_remaining: init.pebbles_count,
-        difficulty: init.difficulty,
-        first_player: first_player.clone(),
-        winner: None,
-    };
+    // Process the first turn if the first player is Program, it may already decide the game
+    let player = init.player.unwrap_or_else(msg::source);
+    let mut game = Game::start(init.difficulty, init.pebbles_count, init.max_pebbles_per_turn, init.misere);
+    finish_game(player, &game.state, &mut game.spectators);
 
-    // Process the first turn if the first player is Program
-    if first_player == Player::Program {
-        let pebbles_to_remove = match state.difficulty {
-            DifficultyLevel::Easy => (get_random_u32() % state.max_pebbles_per_turn) + 1,
-            DifficultyLevel::Hard => find_best_move(state.max_pebbles_per_turn, state.pebbles_remaining),
-        };
-        state.pebbles_remaining -= pebbles_to_remove;
-        state.winner = check_winner(&state);
+    // Save the state, the first game belongs to the requested player or the deployer
+    let mut games = BTreeMap::new();
+    games.insert(player, game);
+    unsafe {
+        GAMES = Some(games);
     }
-
-    // Save the state
-    unsafe { GAME_STATE = Some(state) };
 }
 
 #[no_mangle]
 extern fn handle() {
     let action: PebblesAction = msg::load().expect("Unable to load action message");
+    let player = msg::source();
 
-    unsafe {
-        let mut state = GAME_STATE.take().expect("GameState isn't initialized");
+    let games = games();
 
-        // The game is over, no further actions can be processed
-        if state.winner.is_some() {
-            GAME_STATE = Some(state);
-            return;
-        }
+    match action {
+        PebblesAction::Turn(pebbles) => {
+            // Only a restart can be processed without a game in progress
+            let Some(game) = games.get_mut(&player).filter(|game| game.state.winner.is_none()) else {
+                msg::reply(PebblesEvent::GameNotActive, 0).expect("Unable to send message");
+                return;
+            };
 
-        match action {
-            PebblesAction::Turn(pebbles) => {
-                // Check for valid input data
-                if pebbles <= 0 || pebbles > state.max_pebbles_per_turn || pebbles > state.pebbles_remaining {
-                    msg::reply(PebblesEvent::InvalidMove, 0).expect("Unable to send message");
-                    GAME_STATE = Some(state);
-                    return;
-                }
+            let state = &mut game.state;
+            // Check for valid input data
+            if pebbles == 0 || pebbles > state.max_pebbles_per_turn || pebbles > state.pebbles_remaining {
+                msg::reply(PebblesEvent::InvalidMove, 0).expect("Unable to send message");
+                return;
+            }
+
+            // Process the User's turn
+            state.pebbles_remaining -= pebbles;
+            state.user_moves += 1;
+            state.winner = check_winner(state, Player::User);
+            game.history.push(Move {
+                player: Player::User,
+                pebbles,
+            });
+            notify_spectators(player, &game.spectators, PebblesEvent::Turn(pebbles));
+
+            // If the game is over, notify the winner
+            if let Some(winner) = game.state.winner.clone() {
+                finish_game(player, &game.state, &mut game.spectators);
+                msg::reply(PebblesEvent::Won(winner), 0).expect("Unable to send message");
+                return;
+            }
+
+            // Process the Program's turn
+            let pebbles_to_remove = game.program_turn();
+            notify_spectators(player, &game.spectators, PebblesEvent::CounterTurn(pebbles_to_remove));
+            finish_game(player, &game.state, &mut game.spectators);
+
+            // Notify the user of the Program's turn
+            msg::reply(PebblesEvent::CounterTurn(pebbles_to_remove), 0).expect("Unable to send message");
+        },
+        PebblesAction::GiveUp => {
+            // Only a restart can be processed without a game in progress
+            let Some(game) = games.get_mut(&player).filter(|game| game.state.winner.is_none()) else {
+                msg::reply(PebblesEvent::GameNotActive, 0).expect("Unable to send message");
+                return;
+            };
+
+            // Handle GiveUp action
+            game.state.winner = Some(Player::Program);
+            finish_game(player, &game.state, &mut game.spectators);
+            msg::reply(PebblesEvent::Won(Player::Program), 0).expect("Unable to send message");
+        },
+        PebblesAction::Restart { difficulty, pebbles_count, max_pebbles_per_turn, misere } => {
+            // Check for valid input data, the current game is kept otherwise
+            if !is_valid_game(pebbles_count, max_pebbles_per_turn) {
+                msg::reply(PebblesEvent::InvalidParameters, 0).expect("Unable to send message");
+                return;
+            }
 
-                // Process the User's turn
-                state.pebbles_remaining -= pebbles;
-                state.winner = check_winner(&state);
+            // An unfinished game is abandoned, which counts as a loss the same way as giving up,
+            // its spectators get the result before the game is replaced
+            if let Some(game) = games.get_mut(&player).filter(|game| game.state.winner.is_none()) {
+                game.state.winner = Some(Player::Program);
+                finish_game(player, &game.state, &mut game.spectators);
+            }
 
-                // If the game is over, notify the winner
-                if let Some(ref winner) = state.winner {
-                    msg::reply(PebblesEvent::Won(winner.clone()), 0).expect("Unable to send message");
-                    GAME_STATE = Some(state);
-                    return;
+            // Every player has their own game, a restart starts one for a new player.
+            // The program's opening move may already decide a small game
+            let mut game = Game::start(difficulty, pebbles_count, max_pebbles_per_turn, misere);
+            finish_game(player, &game.state, &mut game.spectators);
+            let state = game.state.clone();
+            games.insert(player, game);
+            msg::reply(PebblesEvent::Restarted(state), 0).expect("Unable to send message");
+        },
+        PebblesAction::RetryRewards => {
+            // Failed mints can be retried whether or not the game is over
+            let retried = rewards().retry() + players().retry();
+            msg::reply(PebblesEvent::RewardsRetried(retried), 0).expect("Unable to send message");
+        },
+        PebblesAction::Watch { player: watched } => {
+            let event = match games.get_mut(&watched) {
+                Some(game) if game.state.winner.is_none() => {
+                    if game.spectators.contains(&player) {
+                        PebblesEvent::Watching(watched)
+                    } else if game.spectators.len() >= MAX_SPECTATORS {
+                        PebblesEvent::SpectatorLimitReached
+                    } else {
+                        game.spectators.push(player);
+                        PebblesEvent::Watching(watched)
+                    }
                 }
+                _ => PebblesEvent::GameNotActive,
+            };
+            msg::reply(event, 0).expect("Unable to send message");
+        },
+        PebblesAction::Unwatch { player: watched } => {
+            if let Some(game) = games.get_mut(&watched) {
+                game.spectators.retain(|spectator| *spectator != player);
+            }
+            msg::reply(PebblesEvent::Unwatched(watched), 0).expect("Unable to send message");
+        },
+    }
+}
 
-                // Process the Program's turn
-                let pebbles_to_remove = match state.difficulty {
-                    DifficultyLevel::Easy => (get_random_u32() % state.max_pebbles_per_turn) + 1,
-                    DifficultyLevel::Hard => find_best_move(state.max_pebbles_per_turn, state.pebbles_remaining),
-                };
-                state.pebbles_remaining -= pebbles_to_remove;
-                state.winner = check_winner(&state);
+/// Settles replies from the token programs, recording mints that failed
+#[no_mangle]
+extern fn handle_reply() {
+    let rewards = rewards();
+    let players = players();
+    let reply_to = msg::reply_to().expect("Unable to get reply_to");
+    let minted = msg::reply_code().map(|code| code.is_success()).unwrap_or(false);
 
-                // Notify the user of the Program's turn
-                msg::reply(PebblesEvent::CounterTurn(pebbles_to_remove), 0).expect("Unable to send message");
-            },
-            PebblesAction::GiveUp => {
-                // Handle GiveUp action
-                state.winner = Some(Player::Program);
-                msg::reply(PebblesEvent::Won(Player::Program), 0).expect("Unable to send message");
-            },
-            PebblesAction::Restart { difficulty, pebbles_count, max_pebbles_per_turn } => {
-                // Handle Restart action
-                state = GameState {
-                    difficulty,
-                    pebbles_count,
-                    max_pebbles_per_turn,
-                    pebbles_remaining: pebbles_count,
-                    first_player: if get_random_u32() % 2 == 0 { Player::User } else { Player::Program },
-                    winner: None,
-                };
-            },
+    if let Some(reward) = rewards.pending.remove(&reply_to) {
+        if !minted {
+            msg::send(reward.player, PebblesEvent::RewardFailed(reward.clone()), 0).expect("Unable to send message");
+            rewards.failed.push(reward);
+        }
+    } else if let Some((player, achievement)) = players.pending.remove(&reply_to) {
+        if !minted {
+            msg::send(player, PebblesEvent::AchievementFailed(achievement), 0).expect("Unable to send message");
+            players.failed.push((player, achievement));
         }
-
-        GAME_STATE = Some(state);
     }
 }
 
-/// Returns the GameState structure using the msg::reply function
+/// Answers a StateQuery using the msg::reply function
 #[no_mangle]
 extern fn state() {
-    let game_state = unsafe { GAME_STATE.as_ref().expect("Game state not initialized") };
-    msg::reply(game_state, 0).expect("Failed to reply with game state");
+    let query: StateQuery = msg::load().expect("Unable to load state query");
+    let games = games();
+    let players = players();
+
+    let reply = match query {
+        StateQuery::Game(player) => StateReply::Game(games.get(&player).map(|game| game.state.clone())),
+        StateQuery::LegalMoves(player) => {
+            StateReply::LegalMoves(games.get(&player).map(|game| game.state.legal_moves()).unwrap_or_default())
+        }
+        StateQuery::IsWinningPosition { remaining, k, misere } => {
+            StateReply::IsWinningPosition(is_winning_position(remaining, k, misere))
+        },
+        StateQuery::History(player) => {
+            StateReply::History(games.get(&player).map(|game| game.history.clone()).unwrap_or_default())
+        }
+        StateQuery::Stats(player) => StateReply::Stats(
+            players
+                .progress
+                .get(&player)
+                .map(|progress| progress.stats.clone())
+                .unwrap_or_default(),
+        ),
+        StateQuery::Achievements(player) => StateReply::Achievements(
+            players
+                .progress
+                .get(&player)
+                .map(|progress| progress.earned.iter().copied().collect())
+                .unwrap_or_default(),
+        ),
+    };
+    msg::reply(reply, 0).expect("Failed to reply with state");
 }
 
 fn get_random_u32() -> u32 {
@@ -124,18 +393,57 @@ fn get_random_u32() -> u32 {
     u32::from_le_bytes([hash[0], hash[1], hash[2], hash[3]])
 }
 
-fn check_winner(state: &GameState) -> Option<Player> {
+/// Pushes an event of the player's game to everyone watching it
+fn notify_spectators(player: ActorId, spectators: &[ActorId], event: PebblesEvent) {
+    for spectator in spectators {
+        let event = SpectatorEvent {
+            game: player,
+            event: event.clone(),
+        };
+        msg::send(*spectator, event, 0).expect("Unable to send message");
+    }
+}
+
+/// Pays out rewards and tracks stats and achievements once a game has a winner,
+/// spectators get the result and are unsubscribed
+fn finish_game(player: ActorId, state: &GameState, spectators: &mut Vec<ActorId>) {
+    let Some(winner) = &state.winner else {
+        return;
+    };
+    notify_spectators(player, spectators, PebblesEvent::Won(winner.clone()));
+    spectators.clear();
+
+    players().record(player, state);
+
+    if state.winner != Some(Player::User) {
+        return;
+    }
+    let rewards = rewards();
+    let Some(amount) = rewards.config.as_ref().and_then(|config| config.amount(state)) else {
+        return;
+    };
+    rewards.mint(Reward { player, amount });
+}
+
+/// The player who took the last pebble wins, or loses in misère play
+fn check_winner(state: &GameState, last_mover: Player) -> Option<Player> {
     if state.pebbles_remaining == 0 {
-        Some(match state.first_player {
+        let other = match last_mover {
             Player::User => Player::Program,
             Player::Program => Player::User,
-        })
+        };
+        Some(if state.misere { other } else { last_mover })
     } else {
         None
     }
 }
 
-fn find_best_move(max_pebbles_per_turn: u32, pebbles_remaining: u32) -> u32 {
+fn find_best_move(max_pebbles_per_turn: u32, pebbles_remaining: u32, misere: bool) -> u32 {
+    if misere {
+        // Leave the opponent one more than a multiple of (max + 1)
+        let pebbles = (pebbles_remaining - 1) % (max_pebbles_per_turn + 1);
+        return pebbles.max(1);
+    }
     if pebbles_remaining % (max_pebbles_per_turn + 1) > 0 {
         pebbles_remaining % (max_pebbles_per_turn + 1)
     } else {
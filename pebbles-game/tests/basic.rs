@@ -1,9 +1,88 @@
 #[cfg(test)]
 mod tests {
     use super::*;
-    use gstd::prelude::*;
-    use gtest::{Program, System};
+    use gstd::{prelude::*, ActorId};
+    use gtest::{Log, Program, System, WasmProgram};
     use pebbles_game_io::*;
+    use std::collections::BTreeMap;
+
+    const FT_ID: u64 = 100;
+    const NFT_ID: u64 = 101;
+
+    /// Minimal stand-in for a fungible-token program
+    #[derive(Debug, Default)]
+    struct FungibleToken {
+        balances: BTreeMap<ActorId, u128>,
+        // Number of mint requests to reject before accepting them
+        reject: u32,
+    }
+
+    impl WasmProgram for FungibleToken {
+        fn init(&mut self, _payload: Vec<u8>) -> Result<Option<Vec<u8>>, &'static str> {
+            Ok(None)
+        }
+
+        fn handle(&mut self, payload: Vec<u8>) -> Result<Option<Vec<u8>>, &'static str> {
+            let FTAction::Mint { to, amount } =
+                FTAction::decode(&mut &payload[..]).map_err(|_| "Unable to decode FTAction")?;
+            if self.reject > 0 {
+                self.reject -= 1;
+                return Err("Mint rejected");
+            }
+            *self.balances.entry(to).or_default() += amount;
+            Ok(Some(FTEvent::Minted { to, amount }.encode()))
+        }
+
+        fn handle_reply(&mut self, _payload: Vec<u8>) -> Result<(), &'static str> {
+            Ok(())
+        }
+
+        fn handle_signal(&mut self, _payload: Vec<u8>) -> Result<(), &'static str> {
+            Ok(())
+        }
+
+        fn state(&mut self) -> Result<Vec<u8>, &'static str> {
+            let balances: Vec<(ActorId, u128)> = self.balances.iter().map(|(k, v)| (*k, *v)).collect();
+            Ok(balances.encode())
+        }
+    }
+
+    /// Minimal stand-in for an NFT program
+    #[derive(Debug, Default)]
+    struct NonFungibleToken {
+        minted: Vec<(ActorId, Achievement)>,
+        // Number of mint requests to reject before accepting them
+        reject: u32,
+    }
+
+    impl WasmProgram for NonFungibleToken {
+        fn init(&mut self, _payload: Vec<u8>) -> Result<Option<Vec<u8>>, &'static str> {
+            Ok(None)
+        }
+
+        fn handle(&mut self, payload: Vec<u8>) -> Result<Option<Vec<u8>>, &'static str> {
+            let NFTAction::Mint { to, token_metadata } =
+                NFTAction::decode(&mut &payload[..]).map_err(|_| "Unable to decode NFTAction")?;
+            if self.reject > 0 {
+                self.reject -= 1;
+                return Err("Mint rejected");
+            }
+            self.minted.push((to, token_metadata.achievement));
+            Ok(None)
+        }
+
+        fn handle_reply(&mut self, _payload: Vec<u8>) -> Result<(), &'static str> {
+            Ok(())
+        }
+
+        fn handle_signal(&mut self, _payload: Vec<u8>) -> Result<(), &'static str> {
+            Ok(())
+        }
+
+        fn state(&mut self) -> Result<Vec<u8>, &'static str> {
+            Ok(self.minted.encode())
+        }
+    }
 
     fn create_system_and_user() -> (System, u64) {
         let sys = System::new();
@@ -13,6 +92,64 @@ mod tests {
         (sys, user_id)
     }
 
+    fn read_game_state(program: &Program, user_id: u64) -> GameState {
+        match program.read_state(StateQuery::Game(user_id.into())).expect("Failed to read state") {
+            StateReply::Game(Some(state)) => state,
+            reply => panic!("Unexpected state reply: {:?}", reply),
+        }
+    }
+
+    fn create_rewarded_game(sys: &System, user_id: u64, reject: u32, pebbles_count: u32) -> (Program<'_>, Program<'_>) {
+        let ft = Program::mock_with_id(sys, FT_ID, FungibleToken { reject, ..Default::default() });
+        ft.send_bytes(user_id, []);
+
+        let program = Program::current(sys);
+        let init_msg = PebblesInit {
+            difficulty: DifficultyLevel::Easy,
+            pebbles_count: 2,
+            max_pebbles_per_turn: 1,
+            misere: false,
+            reward: Some(RewardConfig {
+                ft_program: FT_ID.into(),
+                easy_reward: 100,
+                hard_reward: 300,
+                speed_bonus: 50,
+                min_moves: 3,
+            }),
+            nft_program: None,
+            player: None,
+        };
+        program.send_bytes(user_id, init_msg.encode());
+        restart_until_first(&program, user_id, DifficultyLevel::Easy, false, pebbles_count, Player::Program);
+
+        (ft, program)
+    }
+
+    // With one pebble taken per turn, the player who moves second takes the last one of an even pile
+    fn restart_until_first(
+        program: &Program,
+        user_id: u64,
+        difficulty: DifficultyLevel,
+        misere: bool,
+        pebbles_count: u32,
+        first_player: Player,
+    ) {
+        for _ in 0..32 {
+            let restart_action = PebblesAction::Restart {
+                difficulty: difficulty.clone(),
+                pebbles_count,
+                max_pebbles_per_turn: 1,
+                misere,
+            };
+            program.send_bytes(user_id, restart_action.encode());
+
+            if read_game_state(program, user_id).first_player == first_player {
+                return;
+            }
+        }
+        panic!("{:?} never moved first", first_player);
+    }
+
     #[test]
     fn test_init_success() {
         let (sys, user_id) = create_system_and_user();
@@ -22,12 +159,16 @@ mod tests {
             difficulty: DifficultyLevel::Easy,
             pebbles_count: 10,
             max_pebbles_per_turn: 3,
+            misere: false,
+            reward: None,
+            nft_program: None,
+            player: None,
         };
 
         program.send_bytes(user_id, init_msg.encode());
 
         // Check the initial state
-        let state: GameState = program.read_state(()).expect("Failed to read state");
+        let state = read_game_state(&program, user_id);
         println!("{:?}", state);
         assert_eq!(state.pebbles_count, 10);
         assert_eq!(state.max_pebbles_per_turn, 3);
@@ -44,6 +185,10 @@ mod tests {
             difficulty: DifficultyLevel::Easy,
             pebbles_count: 10,
             max_pebbles_per_turn: 3,
+            misere: false,
+            reward: None,
+            nft_program: None,
+            player: None,
         };
 
         program.send_bytes(user_id, init_msg.encode());
@@ -52,7 +197,7 @@ mod tests {
         program.send_bytes(user_id, turn_action.encode());
 
         // Check the state after the turn
-        let state: GameState = program.read_state(()).expect("Failed to read state");
+        let state = read_game_state(&program, user_id);
         println!("State: {:?}", state);
         assert!(state.pebbles_remaining <= 7); // Depending on who plays first
         assert!(state.first_player == Player::Program || state.first_player == Player::User);
@@ -67,6 +212,10 @@ mod tests {
             difficulty: DifficultyLevel::Easy,
             pebbles_count: 1,
             max_pebbles_per_turn: 1,
+            misere: false,
+            reward: None,
+            nft_program: None,
+            player: None,
         };
 
         program.send_bytes(user_id, init_msg.encode());
@@ -74,10 +223,10 @@ mod tests {
         let turn_action = PebblesAction::Turn(1);
         program.send_bytes(user_id, turn_action.encode());
 
-        // Check the state after the turn to determine the winner
-        let state: GameState = program.read_state(()).expect("Failed to read state");
+        // Whoever moves first takes the only pebble
+        let state = read_game_state(&program, user_id);
         println!("State: {:?}", state);
-        assert_eq!(state.winner, Some(Player::User)); // Adjust based on your game logic
+        assert_eq!(state.winner, Some(state.first_player));
     }
 
     #[test]
@@ -89,6 +238,10 @@ mod tests {
             difficulty: DifficultyLevel::Easy,
             pebbles_count: 10,
             max_pebbles_per_turn: 3,
+            misere: false,
+            reward: None,
+            nft_program: None,
+            player: None,
         };
 
         program.send_bytes(user_id, init_msg.encode());
@@ -97,16 +250,20 @@ mod tests {
             difficulty: DifficultyLevel::Hard,
             pebbles_count: 20,
             max_pebbles_per_turn: 5,
+            misere: false,
         };
 
-        program.send_bytes(user_id, restart_action.encode());
+        let res = program.send_bytes(user_id, restart_action.encode());
 
-        // Check the state after restarting the game
-        let state: GameState = program.read_state(()).expect("Failed to read state");
+        // Check the state after restarting the game, it's also sent back in the reply
+        let state = read_game_state(&program, user_id);
         println!("{:?}", state);
+        assert!(res.contains(&Log::builder().dest(user_id).payload(PebblesEvent::Restarted(state.clone()))));
         assert_eq!(state.pebbles_count, 20);
         assert_eq!(state.max_pebbles_per_turn, 5);
-        assert_eq!(state.pebbles_remaining, 20);
+        // On Hard the program opens by leaving a multiple of 6
+        let remaining = if state.first_player == Player::Program { 18 } else { 20 };
+        assert_eq!(state.pebbles_remaining, remaining);
     }
 
     #[test]
@@ -118,6 +275,10 @@ mod tests {
             difficulty: DifficultyLevel::Easy,
             pebbles_count: 10,
             max_pebbles_per_turn: 3,
+            misere: false,
+            reward: None,
+            nft_program: None,
+            player: None,
         };
 
         program.send_bytes(user_id, init_msg.encode());
@@ -126,8 +287,404 @@ mod tests {
         program.send_bytes(user_id, give_up_action.encode());
 
         // Check the state after giving up
-        let state: GameState = program.read_state(()).expect("Failed to read state");
+        let state = read_game_state(&program, user_id);
         println!("{:?}", state);
         assert_eq!(state.winner, Some(Player::Program));
     }
+
+    #[test]
+    fn test_restart_counts_as_loss() {
+        let (sys, user_id) = create_system_and_user();
+        let program = Program::current(&sys);
+
+        let init_msg = PebblesInit {
+            difficulty: DifficultyLevel::Easy,
+            pebbles_count: 10,
+            max_pebbles_per_turn: 3,
+            misere: false,
+            reward: None,
+            nft_program: None,
+            player: None,
+        };
+        program.send_bytes(user_id, init_msg.encode());
+
+        // Abandoning a losing position breaks the streak like giving up does
+        let restart_action = PebblesAction::Restart {
+            difficulty: DifficultyLevel::Easy,
+            pebbles_count: 10,
+            max_pebbles_per_turn: 3,
+            misere: false,
+        };
+        program.send_bytes(user_id, restart_action.encode());
+
+        let reply: StateReply = program.read_state(StateQuery::Stats(user_id.into())).expect("Failed to read state");
+        let expected = PlayerStats {
+            games_played: 1,
+            wins: 0,
+            losses: 1,
+            win_streak: 0,
+            best_streak: 0,
+        };
+        assert!(matches!(reply, StateReply::Stats(stats) if stats == expected));
+    }
+
+    #[test]
+    fn test_win_mints_reward() {
+        let (sys, user_id) = create_system_and_user();
+        let (ft, program) = create_rewarded_game(&sys, user_id, 0, 6);
+
+        for _ in 0..3 {
+            program.send_bytes(user_id, PebblesAction::Turn(1).encode());
+        }
+
+        let state = read_game_state(&program, user_id);
+        assert_eq!(state.winner, Some(Player::User));
+        assert_eq!(state.user_moves, 3);
+
+        // Easy reward plus the whole speed bonus, three moves is as fast as six pebbles allow
+        let balances: Vec<(ActorId, u128)> = ft.read_state(()).expect("Failed to read balances");
+        assert_eq!(balances, vec![(user_id.into(), 150)]);
+    }
+
+    #[test]
+    fn test_small_game_earns_nothing() {
+        let (sys, user_id) = create_system_and_user();
+        let (ft, program) = create_rewarded_game(&sys, user_id, 0, 2);
+
+        program.send_bytes(user_id, PebblesAction::Turn(1).encode());
+        assert_eq!(read_game_state(&program, user_id).winner, Some(Player::User));
+
+        // Optimal play finishes two pebbles in a single move, below the configured minimum
+        let balances: Vec<(ActorId, u128)> = ft.read_state(()).expect("Failed to read balances");
+        assert!(balances.is_empty());
+    }
+
+    #[test]
+    fn test_failed_reward_is_retried() {
+        let (sys, user_id) = create_system_and_user();
+        let (ft, program) = create_rewarded_game(&sys, user_id, 1, 6);
+
+        let reward = Reward {
+            player: user_id.into(),
+            amount: 150,
+        };
+        for _ in 0..2 {
+            program.send_bytes(user_id, PebblesAction::Turn(1).encode());
+        }
+        let res = program.send_bytes(user_id, PebblesAction::Turn(1).encode());
+        assert!(res.contains(&Log::builder().dest(user_id).payload(PebblesEvent::RewardFailed(reward))));
+
+        let balances: Vec<(ActorId, u128)> = ft.read_state(()).expect("Failed to read balances");
+        assert!(balances.is_empty());
+
+        let res = program.send_bytes(user_id, PebblesAction::RetryRewards.encode());
+        assert!(res.contains(&Log::builder().dest(user_id).payload(PebblesEvent::RewardsRetried(1))));
+
+        let balances: Vec<(ActorId, u128)> = ft.read_state(()).expect("Failed to read balances");
+        assert_eq!(balances, vec![(user_id.into(), 150)]);
+    }
+
+    #[test]
+    fn test_achievement_minted_once() {
+        let (sys, user_id) = create_system_and_user();
+        let nft = Program::mock_with_id(&sys, NFT_ID, NonFungibleToken::default());
+        nft.send_bytes(user_id, []);
+
+        let program = Program::current(&sys);
+        let init_msg = PebblesInit {
+            difficulty: DifficultyLevel::Hard,
+            pebbles_count: 2,
+            max_pebbles_per_turn: 1,
+            misere: false,
+            reward: None,
+            nft_program: Some(NFT_ID.into()),
+            player: None,
+        };
+        program.send_bytes(user_id, init_msg.encode());
+
+        // A win decided by the coin flip of a tiny game earns nothing
+        restart_until_first(&program, user_id, DifficultyLevel::Hard, false, 2, Player::Program);
+        program.send_bytes(user_id, PebblesAction::Turn(1).encode());
+        assert_eq!(read_game_state(&program, user_id).winner, Some(Player::User));
+        let minted: Vec<(ActorId, Achievement)> = nft.read_state(()).expect("Failed to read minted tokens");
+        assert!(minted.is_empty());
+
+        // Win twice on Hard, the achievement is only awarded for the first win
+        for _ in 0..2 {
+            restart_until_first(&program, user_id, DifficultyLevel::Hard, false, 6, Player::Program);
+            for _ in 0..3 {
+                program.send_bytes(user_id, PebblesAction::Turn(1).encode());
+            }
+            assert_eq!(read_game_state(&program, user_id).winner, Some(Player::User));
+        }
+
+        let minted: Vec<(ActorId, Achievement)> = nft.read_state(()).expect("Failed to read minted tokens");
+        assert_eq!(minted, vec![(user_id.into(), Achievement::FirstHardWin)]);
+
+        let reply: StateReply = program
+            .read_state(StateQuery::Achievements(user_id.into()))
+            .expect("Failed to read state");
+        assert!(matches!(reply, StateReply::Achievements(earned) if earned == vec![Achievement::FirstHardWin]));
+    }
+
+    #[test]
+    fn test_failed_achievement_is_retried() {
+        let (sys, user_id) = create_system_and_user();
+        let nft = Program::mock_with_id(&sys, NFT_ID, NonFungibleToken { reject: 1, ..Default::default() });
+        nft.send_bytes(user_id, []);
+
+        let program = Program::current(&sys);
+        let init_msg = PebblesInit {
+            difficulty: DifficultyLevel::Hard,
+            pebbles_count: 2,
+            max_pebbles_per_turn: 1,
+            misere: false,
+            reward: None,
+            nft_program: Some(NFT_ID.into()),
+            player: None,
+        };
+        program.send_bytes(user_id, init_msg.encode());
+        restart_until_first(&program, user_id, DifficultyLevel::Hard, false, 6, Player::Program);
+
+        // A rejected mint doesn't revert the winning turn
+        for _ in 0..2 {
+            program.send_bytes(user_id, PebblesAction::Turn(1).encode());
+        }
+        let res = program.send_bytes(user_id, PebblesAction::Turn(1).encode());
+        assert!(!res.main_failed());
+        assert!(res.contains(&Log::builder().dest(user_id).payload(PebblesEvent::Won(Player::User))));
+        let failed = PebblesEvent::AchievementFailed(Achievement::FirstHardWin);
+        assert!(res.contains(&Log::builder().dest(user_id).payload(failed)));
+
+        let minted: Vec<(ActorId, Achievement)> = nft.read_state(()).expect("Failed to read minted tokens");
+        assert!(minted.is_empty());
+
+        let res = program.send_bytes(user_id, PebblesAction::RetryRewards.encode());
+        assert!(res.contains(&Log::builder().dest(user_id).payload(PebblesEvent::RewardsRetried(1))));
+
+        let minted: Vec<(ActorId, Achievement)> = nft.read_state(()).expect("Failed to read minted tokens");
+        assert_eq!(minted, vec![(user_id.into(), Achievement::FirstHardWin)]);
+    }
+
+    #[test]
+    fn test_misere_win() {
+        let (sys, user_id) = create_system_and_user();
+        let nft = Program::mock_with_id(&sys, NFT_ID, NonFungibleToken::default());
+        nft.send_bytes(user_id, []);
+
+        let program = Program::current(&sys);
+        let init_msg = PebblesInit {
+            difficulty: DifficultyLevel::Easy,
+            pebbles_count: 2,
+            max_pebbles_per_turn: 1,
+            misere: true,
+            reward: None,
+            nft_program: Some(NFT_ID.into()),
+            player: None,
+        };
+        program.send_bytes(user_id, init_msg.encode());
+
+        // The user opens, so the program is left to take the last pebble and loses
+        for _ in 0..2 {
+            restart_until_first(&program, user_id, DifficultyLevel::Easy, true, 6, Player::User);
+            for _ in 0..2 {
+                program.send_bytes(user_id, PebblesAction::Turn(1).encode());
+            }
+            let res = program.send_bytes(user_id, PebblesAction::Turn(1).encode());
+            assert!(res.contains(&Log::builder().dest(user_id).payload(PebblesEvent::CounterTurn(1))));
+            assert_eq!(read_game_state(&program, user_id).winner, Some(Player::User));
+        }
+
+        let minted: Vec<(ActorId, Achievement)> = nft.read_state(()).expect("Failed to read minted tokens");
+        assert_eq!(minted, vec![(user_id.into(), Achievement::MisereWin)]);
+    }
+
+    #[test]
+    fn test_state_queries() {
+        let (sys, user_id) = create_system_and_user();
+        let (_ft, program) = create_rewarded_game(&sys, user_id, 0, 2);
+
+        let reply: StateReply = program.read_state(StateQuery::LegalMoves(user_id.into())).expect("Failed to read state");
+        assert!(matches!(reply, StateReply::LegalMoves(moves) if moves == vec![1]));
+
+        program.send_bytes(user_id, PebblesAction::Turn(1).encode());
+
+        let reply: StateReply = program.read_state(StateQuery::History(user_id.into())).expect("Failed to read state");
+        let StateReply::History(history) = reply else {
+            panic!("Unexpected state reply: {:?}", reply);
+        };
+        assert_eq!(
+            history,
+            vec![
+                Move { player: Player::Program, pebbles: 1 },
+                Move { player: Player::User, pebbles: 1 }
+            ]
+        );
+
+        // The game is over, so there is nothing left to take
+        let reply: StateReply = program.read_state(StateQuery::LegalMoves(user_id.into())).expect("Failed to read state");
+        assert!(matches!(reply, StateReply::LegalMoves(moves) if moves.is_empty()));
+
+        // Games abandoned while waiting for the program to open count as losses,
+        // and a two-pebble win is too small to count toward the streak
+        let reply: StateReply = program.read_state(StateQuery::Stats(user_id.into())).expect("Failed to read state");
+        let StateReply::Stats(stats) = reply else {
+            panic!("Unexpected state reply: {:?}", reply);
+        };
+        assert_eq!(stats.wins, 1);
+        assert_eq!(stats.games_played, stats.losses + 1);
+        assert_eq!(stats.win_streak, 0);
+
+        for (remaining, k, misere, winning) in [
+            (10, 3, false, true),
+            (8, 3, false, false),
+            (1, 1, false, true),
+            (6, 2, false, false),
+            (1, 1, true, false),
+            (5, 3, true, false),
+            (4, 3, true, true),
+            (8, 3, true, true),
+        ] {
+            let reply: StateReply = program
+                .read_state(StateQuery::IsWinningPosition { remaining, k, misere })
+                .expect("Failed to read state");
+            assert!(matches!(reply, StateReply::IsWinningPosition(result) if result == winning));
+        }
+    }
+
+    #[test]
+    fn test_games_are_per_player() {
+        let (sys, user_id) = create_system_and_user();
+        let other_user = 2;
+        sys.mint_to(other_user, 10000000000000);
+        let program = Program::current(&sys);
+
+        let init_msg = PebblesInit {
+            difficulty: DifficultyLevel::Easy,
+            pebbles_count: 10,
+            max_pebbles_per_turn: 3,
+            misere: false,
+            reward: None,
+            nft_program: None,
+            player: None,
+        };
+        program.send_bytes(user_id, init_msg.encode());
+
+        // Only the deployer has a game until someone else starts one
+        let reply: StateReply = program.read_state(StateQuery::Game(other_user.into())).expect("Failed to read state");
+        assert!(matches!(reply, StateReply::Game(None)));
+        for action in [PebblesAction::Turn(1), PebblesAction::GiveUp] {
+            let res = program.send_bytes(other_user, action.encode());
+            assert!(res.contains(&Log::builder().dest(other_user).payload(PebblesEvent::GameNotActive)));
+        }
+
+        // Unplayable parameters don't start a game
+        for (pebbles_count, max_pebbles_per_turn) in [(0, 1), (3, 0), (3, 4)] {
+            let restart_action = PebblesAction::Restart {
+                difficulty: DifficultyLevel::Easy,
+                pebbles_count,
+                max_pebbles_per_turn,
+                misere: false,
+            };
+            let res = program.send_bytes(other_user, restart_action.encode());
+            assert!(res.contains(&Log::builder().dest(other_user).payload(PebblesEvent::InvalidParameters)));
+        }
+        let reply: StateReply = program.read_state(StateQuery::Game(other_user.into())).expect("Failed to read state");
+        assert!(matches!(reply, StateReply::Game(None)));
+
+        let restart_action = PebblesAction::Restart {
+            difficulty: DifficultyLevel::Hard,
+            pebbles_count: 20,
+            max_pebbles_per_turn: 5,
+            misere: false,
+        };
+        program.send_bytes(other_user, restart_action.encode());
+
+        assert_eq!(read_game_state(&program, other_user).pebbles_count, 20);
+        assert_eq!(read_game_state(&program, user_id).pebbles_count, 10);
+    }
+
+    #[test]
+    fn test_spectators_follow_game() {
+        let (sys, user_id) = create_system_and_user();
+        let spectator = 3;
+        sys.mint_to(spectator, 10000000000000);
+        let (_ft, program) = create_rewarded_game(&sys, user_id, 0, 2);
+
+        let res = program.send_bytes(spectator, PebblesAction::Watch { player: user_id.into() }.encode());
+        assert!(res.contains(&Log::builder().dest(spectator).payload(PebblesEvent::Watching(user_id.into()))));
+
+        let res = program.send_bytes(user_id, PebblesAction::Turn(1).encode());
+        for event in [PebblesEvent::Turn(1), PebblesEvent::Won(Player::User)] {
+            let event = SpectatorEvent {
+                game: user_id.into(),
+                event,
+            };
+            assert!(res.contains(&Log::builder().dest(spectator).payload(event)));
+        }
+
+        // The subscription ended with the game
+        let res = program.send_bytes(spectator, PebblesAction::Watch { player: user_id.into() }.encode());
+        assert!(res.contains(&Log::builder().dest(spectator).payload(PebblesEvent::GameNotActive)));
+    }
+
+    #[test]
+    fn test_spectators_see_restart() {
+        let (sys, user_id) = create_system_and_user();
+        let spectator = 3;
+        sys.mint_to(spectator, 10000000000000);
+        let (_ft, program) = create_rewarded_game(&sys, user_id, 0, 6);
+
+        program.send_bytes(spectator, PebblesAction::Watch { player: user_id.into() }.encode());
+
+        // Restarting abandons the running game, which the spectator sees as lost
+        let restart_action = PebblesAction::Restart {
+            difficulty: DifficultyLevel::Easy,
+            pebbles_count: 6,
+            max_pebbles_per_turn: 1,
+            misere: false,
+        };
+        let res = program.send_bytes(user_id, restart_action.encode());
+        let event = SpectatorEvent {
+            game: user_id.into(),
+            event: PebblesEvent::Won(Player::Program),
+        };
+        assert!(res.contains(&Log::builder().dest(spectator).payload(event)));
+
+        // The new game starts without spectators
+        let res = program.send_bytes(user_id, PebblesAction::Turn(1).encode());
+        assert!(!res.contains(&Log::builder().dest(spectator)));
+    }
+
+    #[test]
+    fn test_spectator_limit() {
+        let (sys, user_id) = create_system_and_user();
+        let program = Program::current(&sys);
+
+        let init_msg = PebblesInit {
+            difficulty: DifficultyLevel::Easy,
+            pebbles_count: 10,
+            max_pebbles_per_turn: 3,
+            misere: false,
+            reward: None,
+            nft_program: None,
+            player: None,
+        };
+        program.send_bytes(user_id, init_msg.encode());
+
+        let watch_action = PebblesAction::Watch { player: user_id.into() };
+        for spectator in 200..200 + MAX_SPECTATORS as u64 {
+            let res = program.send_bytes(spectator, watch_action.encode());
+            assert!(res.contains(&Log::builder().dest(spectator).payload(PebblesEvent::Watching(user_id.into()))));
+        }
+
+        let spectator = 200 + MAX_SPECTATORS as u64;
+        let res = program.send_bytes(spectator, watch_action.encode());
+        assert!(res.contains(&Log::builder().dest(spectator).payload(PebblesEvent::SpectatorLimitReached)));
+
+        // A freed slot can be taken again
+        program.send_bytes(200, PebblesAction::Unwatch { player: user_id.into() }.encode());
+        let res = program.send_bytes(spectator, watch_action.encode());
+        assert!(res.contains(&Log::builder().dest(spectator).payload(PebblesEvent::Watching(user_id.into()))));
+    }
 }
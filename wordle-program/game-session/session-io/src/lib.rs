@@ -2,6 +2,11 @@
 
 use gmeta::{In, InOut, Metadata, Out};
 use gstd::{collections::HashMap, prelude::*, ActorId, MessageId};
+// wordle 程序的消息类型直接复用，结果可以原样转发
+pub use wordle_io::{
+    max_tries, Action as WordleAction, BoardFeedback, Event as WordleEvent, Feedback, GameMode,
+    GameResult, HardModeViolation, LetterResult, MAX_BOARDS, MAX_TRIES,
+};
 
 #[derive(Debug, Default, Clone, Encode, Decode, TypeInfo)]
 pub struct GameSessionState {
@@ -34,7 +39,17 @@ impl From<GameSessionInit> for GameSession {
 
 #[derive(Debug, Clone, Encode, Decode, TypeInfo)]
 pub enum GameSessionAction {
-    StartGame,
+    /// Starts a game with a word of `length` letters from the word bank of the language, e.g. `en`.
+    /// With several `boards` there is a word to guess on each of them, as in Quordle.
+    /// A `category` limits the words to one theme, e.g. the vocabulary of a lesson.
+    StartGame {
+        language: String,
+        length: u8,
+        hard_mode: bool,
+        mode: GameMode,
+        boards: u8,
+        category: Option<String>,
+    },
     CheckWord {
         word: String,
     },
@@ -44,20 +59,23 @@ pub enum GameSessionAction {
     },
 }
 
-#[derive(Debug, Clone, Encode, Decode, TypeInfo)]
-pub enum WordleAction {
-    StartGame { user: ActorId },
-    CheckWord { user: ActorId, word: String },
-}
-
 #[derive(Debug, Clone, Encode, Decode, TypeInfo)]
 pub enum GameSessionEvent {
-    StartSuccess,
+    /// Carries the commitment to the secret word of every board, see `wordle_io::verify_reveal`.
+    StartSuccess {
+        commitments: Vec<[u8; 32]>,
+    },
     CheckWordResult {
-        correct_positions: Vec<u8>,
-        contained_in_word: Vec<u8>,
+        feedback: Feedback,
     },
     GameOver(GameStatus),
+    InvalidWord,
+    HardModeViolation(HardModeViolation),
+    GameInProgress,
+    /// Feedback for every board that was still unsolved before the guess.
+    BoardsCheckResult {
+        feedback: Vec<BoardFeedback>,
+    },
 }
 
 #[derive(Debug, Clone, Encode, Decode, TypeInfo)]
@@ -66,40 +84,27 @@ pub enum GameStatus {
     Lose,
 }
 
-#[derive(Debug, Clone, Encode, Decode, TypeInfo)]
-pub enum WordleEvent {
-    GameStarted {
-        user: ActorId,
-    },
-    WordChecked {
-        user: ActorId,
-        correct_positions: Vec<u8>,
-        contained_in_word: Vec<u8>,
-    },
-}
-
-impl WordleEvent {
-    pub fn get_user(&self) -> &ActorId {
-        match self {
-            WordleEvent::GameStarted { user } => user,
-            WordleEvent::WordChecked { user, .. } => user,
-        }
-    }
-}
+// 从 WordleEvent 转换为 GameSessionEvent，与玩家会话无关的事件无法转换
+impl TryFrom<&WordleEvent> for GameSessionEvent {
+    type Error = ();
 
-// 从 WordleEvent 转换为 GameSessionEvent
-impl From<&WordleEvent> for GameSessionEvent {
-    fn from(wordle_event: &WordleEvent) -> Self {
+    fn try_from(wordle_event: &WordleEvent) -> Result<Self, Self::Error> {
         match wordle_event {
-            WordleEvent::GameStarted { .. } => GameSessionEvent::StartSuccess,
-            WordleEvent::WordChecked {
-                correct_positions,
-                contained_in_word,
-                ..
-            } => GameSessionEvent::CheckWordResult {
-                correct_positions: correct_positions.clone(),
-                contained_in_word: contained_in_word.clone(),
-            },
+            WordleEvent::GameStarted { commitments, .. } => Ok(GameSessionEvent::StartSuccess {
+                commitments: commitments.clone(),
+            }),
+            WordleEvent::WordChecked { feedback, .. } => Ok(GameSessionEvent::CheckWordResult {
+                feedback: *feedback,
+            }),
+            WordleEvent::InvalidWord { .. } => Ok(GameSessionEvent::InvalidWord),
+            WordleEvent::HardModeViolation { violation, .. } => {
+                Ok(GameSessionEvent::HardModeViolation(violation.clone()))
+            }
+            WordleEvent::GameInProgress { .. } => Ok(GameSessionEvent::GameInProgress),
+            WordleEvent::BoardsChecked { feedback, .. } => Ok(GameSessionEvent::BoardsCheckResult {
+                feedback: feedback.clone(),
+            }),
+            _ => Err(()),
         }
     }
 }
@@ -121,6 +126,9 @@ pub struct SessionInfo {
     pub original_msg_id: MessageId,
     pub send_to_wordle_msg_id: MessageId,
     pub tries: u8,
+    pub word_length: u8,
+    pub boards: u8,
+    pub solved_boards: u8,
     pub session_status: SessionStatus,
 }
 
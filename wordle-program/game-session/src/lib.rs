@@ -1,6 +1,6 @@
 #![no_std]
 use session_io::*;
-use gstd::{exec, msg, vec, MessageId, debug};
+use gstd::{exec, msg, MessageId, debug};
 
 static mut GAME_SESSION_STATE: Option<GameSession> = None;
 
@@ -32,28 +32,62 @@ extern "C" fn handle() {
     debug!("Current game session state: {:?}", game_session);
 
     match action {
-        GameSessionAction::StartGame => {
+        GameSessionAction::StartGame {
+            language,
+            length,
+            hard_mode,
+            mode,
+            boards,
+            category,
+        } => {
             let session = game_session.sessions.entry(user).or_insert_with(|| {
                 SessionInfo {
                     session_id: msg::id(),
                     original_msg_id: msg::id(),
                     send_to_wordle_msg_id: MessageId::default(),
                     tries: 0,
+                    word_length: length,
+                    boards,
+                    solved_boards: 0,
                     session_status: SessionStatus::Init,
                 }
             });
 
             match &session.session_status {
-                SessionStatus::Init => {
+                SessionStatus::Init | SessionStatus::GameOver(_) => {
                     debug!("{:?}", user);
-                    msg::send(game_session.wordle_program_id, WordleAction::StartGame { user }, 0)
+                    // A restarted session may pick another shape of game
+                    session.session_id = msg::id();
+                    session.original_msg_id = msg::id();
+                    session.word_length = length;
+                    session.boards = boards;
+                    session.solved_boards = 0;
+                    session.tries = 0;
+                    let start_game = WordleAction::StartGame {
+                        user,
+                        language,
+                        length,
+                        hard_mode,
+                        mode,
+                        boards,
+                        category,
+                        force: false,
+                    };
+                    session.send_to_wordle_msg_id = msg::send(game_session.wordle_program_id, start_game, 0)
                         .expect("Unable to send StartGame action to Wordle program");
                     session.session_status = SessionStatus::WaitWordleStartReply;
                     exec::wait();
                 }
-                SessionStatus::ReplyReceived(_) => {
+                SessionStatus::ReplyReceived(WordleEvent::GameInProgress { .. }) => {
+                    // The user has to finish or abandon the unfinished Wordle game first
+                    session.session_status = SessionStatus::Init;
+                    msg::reply(GameSessionEvent::GameInProgress, 0)
+                        .expect("Failed to send GameInProgress event");
+                }
+                SessionStatus::ReplyReceived(WordleEvent::GameStarted { commitments, .. }) => {
+                    let commitments = commitments.clone();
                     session.session_status = SessionStatus::WaitUserInput;
-                    msg::reply(GameSessionEvent::StartSuccess, 0)
+                    msg::reply(GameSessionEvent::StartSuccess { commitments }, 0)
                         .expect("Failed to send StartSuccess event");
 
                     msg::send_delayed(
@@ -77,30 +111,45 @@ extern "C" fn handle() {
             match &session.session_status {
                 SessionStatus::WaitUserInput => {
                     let wordle_action = WordleAction::CheckWord { user, word };
+                    session.original_msg_id = msg::id();
                     session.session_status = SessionStatus::WaitWordleCheckWordReply;
-                    msg::send(game_session.wordle_program_id, wordle_action, 0)
+                    session.send_to_wordle_msg_id = msg::send(game_session.wordle_program_id, wordle_action, 0)
                         .expect("Unable to send CheckWord action to Wordle program");
                     exec::wait();
                 }
-                SessionStatus::ReplyReceived(WordleEvent::WordChecked { correct_positions, contained_in_word ,user: _}) => {
-                    let correct_positions=correct_positions.clone();
-                    let contained_in_word=contained_in_word.clone();
-                    session.tries += 1;
-                    if session.tries >= 6 {
-                        session.session_status = SessionStatus::GameOver(GameStatus::Lose);
-                        msg::reply(GameSessionEvent::GameOver(GameStatus::Lose), 0)
-                            .expect("Failed to send GameOver event");
-                    } else if correct_positions.len() == 5 {
-                        session.session_status = SessionStatus::GameOver(GameStatus::Win);
-                        msg::reply(GameSessionEvent::GameOver(GameStatus::Win), 0)
-                            .expect("Failed to send GameOver event");
-                    } else {
-                        session.session_status = SessionStatus::WaitUserInput;
-                        msg::reply(GameSessionEvent::CheckWordResult {
-                            correct_positions,
-                            contained_in_word,
-                        }, 0).expect("Failed to send CheckWordResult event");
-                    }
+                SessionStatus::ReplyReceived(WordleEvent::WordChecked { feedback, user: _ }) => {
+                    // The feedback is forwarded to the user unchanged
+                    let feedback = *feedback;
+                    let solved = feedback.len() == session.word_length as usize && feedback.is_solved();
+                    check_result(session, solved as u8, GameSessionEvent::CheckWordResult { feedback });
+                }
+                SessionStatus::ReplyReceived(WordleEvent::BoardsChecked { feedback, user: _ }) => {
+                    let solved = feedback
+                        .iter()
+                        .filter(|board| {
+                            board.feedback.len() == session.word_length as usize
+                                && board.feedback.is_solved()
+                        })
+                        .count() as u8;
+                    let event = GameSessionEvent::BoardsCheckResult { feedback: feedback.clone() };
+                    check_result(session, solved, event);
+                }
+                SessionStatus::ReplyReceived(WordleEvent::InvalidWord { .. }) => {
+                    // An unknown word doesn't count as a try
+                    session.session_status = SessionStatus::WaitUserInput;
+                    msg::reply(GameSessionEvent::InvalidWord, 0)
+                        .expect("Failed to send InvalidWord event");
+                }
+                SessionStatus::ReplyReceived(WordleEvent::HardModeViolation { violation, .. }) => {
+                    // Neither does a guess that ignores a revealed hint
+                    let event = GameSessionEvent::HardModeViolation(violation.clone());
+                    session.session_status = SessionStatus::WaitUserInput;
+                    msg::reply(event, 0).expect("Failed to send HardModeViolation event");
+                }
+                SessionStatus::GameOver(status) => {
+                    // Also the answer to a guess that was being checked when the game timed out
+                    msg::reply(GameSessionEvent::GameOver(status.clone()), 0)
+                        .expect("Failed to send GameOver event");
                 }
                 _ => {
                     debug!("Unexpected session status for CheckWord: {:?}", session.session_status);
@@ -109,34 +158,66 @@ extern "C" fn handle() {
         }
 
         GameSessionAction::CheckGameStatus { user, session_id } => {
-            let session = game_session.sessions.get(&user)
+            // Only the delayed message sent when the game started can time it out
+            assert_eq!(msg::source(), exec::program_id(), "Only the game session can check the game status");
+            let session = game_session.sessions.get_mut(&user)
                 .expect("Session not found");
 
-            if session.session_id == session_id {
-                let event = match &session.session_status {
-                    SessionStatus::GameOver(status) => GameSessionEvent::GameOver(status.clone()),
-                    _ => GameSessionEvent::CheckWordResult {
-                        correct_positions: vec![],
-                        contained_in_word: vec![],
-                    },
-                };
-                msg::reply(event, 0).expect("Failed to send reply");
-            } else {
+            if session.session_id != session_id {
                 debug!("Session ID mismatch in CheckGameStatus");
+                return;
+            }
+            // A game still running when the time is up is lost,
+            // the Wordle game is ended as well so it doesn't stay in its memory
+            if !matches!(session.session_status, SessionStatus::GameOver(_)) {
+                session.session_status = SessionStatus::GameOver(GameStatus::Lose);
+                let end_game = WordleAction::EndGame { user, result: GameResult::Lost };
+                msg::send(game_session.wordle_program_id, end_game, 0)
+                    .expect("Unable to send EndGame action to Wordle program");
             }
         }
     }
 }
 
+// Counts a checked guess: the game is won once every board is solved,
+// and lost when the tries run out first
+fn check_result(session: &mut SessionInfo, solved: u8, event: GameSessionEvent) {
+    session.tries += 1;
+    session.solved_boards += solved;
+    if session.solved_boards >= session.boards {
+        session.session_status = SessionStatus::GameOver(GameStatus::Win);
+        msg::reply(GameSessionEvent::GameOver(GameStatus::Win), 0)
+            .expect("Failed to send GameOver event");
+    } else if session.tries as u32 >= max_tries(session.boards) {
+        session.session_status = SessionStatus::GameOver(GameStatus::Lose);
+        msg::reply(GameSessionEvent::GameOver(GameStatus::Lose), 0)
+            .expect("Failed to send GameOver event");
+    } else {
+        session.session_status = SessionStatus::WaitUserInput;
+        msg::reply(event, 0).expect("Failed to send check result event");
+    }
+}
+
 #[no_mangle]
 extern "C" fn handle_reply() {
     let wordle_event: WordleEvent = msg::load().expect("Unable to decode WordleEvent");
     let game_session = get_game_session_mut();
-    let user = wordle_event.get_user();
     debug!("Handling WordleEvent: {:?}", wordle_event);
+    let Some(&user) = wordle_event.user() else {
+        debug!("WordleEvent without a user: {:?}", wordle_event);
+        return;
+    };
 
-    if let Some(session) = game_session.sessions.get_mut(user) {
-        session.session_status = SessionStatus::ReplyReceived(wordle_event);
+    if let Some(session) = game_session.sessions.get_mut(&user) {
+        // Only the reply to the request the user's message waits for, not e.g. to ending a timed out game
+        if msg::reply_to().expect("Failed to get the replied message") != session.send_to_wordle_msg_id {
+            debug!("Reply to another message for user: {:?}", user);
+            return;
+        }
+        // A game that timed out while waiting stays over
+        if !matches!(session.session_status, SessionStatus::GameOver(_)) {
+            session.session_status = SessionStatus::ReplyReceived(wordle_event);
+        }
         debug!("Updated session status: {:?}", session);
         exec::wake(session.original_msg_id).expect("Failed to wake");
     } else {
@@ -1,14 +1,19 @@
 #[cfg(test)]
 mod tests {
     use gstd::{prelude::*, ActorId};
-    use gtest::{Program, System};
+    use gtest::{Log, Program, System};
     use session_io::*;
+    use wordle_io::{InitConfig, StateQuery, StateReply};
 
     const WORDLE_ID: u64 = 1;
     const GAME_SESSION_ID: u64 = 2;
     const USER1: u64 = 10;
 
     fn setup() -> System {
+        setup_with_words(&["house"])
+    }
+
+    fn setup_with_words(words: &[&str]) -> System {
         let sys = System::new();
         sys.init_logger();
 
@@ -22,20 +27,57 @@ mod tests {
         let wordle_id: ActorId = WORDLE_ID.into();
 
         // Initialize the Wordle program
-        assert!(!wordle.send(user_id, wordle_id).main_failed());
+        let config = InitConfig {
+            language: "en".to_string(),
+            words: words.iter().map(|word| word.to_string()).collect(),
+            owner: user_id,
+            allowed_guesses: vec!["hello".to_string(), "wrong".to_string()],
+            daily: None,
+            trusted_callers: vec![GAME_SESSION_ID.into()],
+        };
+        assert!(!wordle.send(user_id, config).main_failed());
         // Initialize the Game Session program
         assert!(!game_session.send(user_id, wordle_id).main_failed());
 
         sys
     }
 
+    fn start_game() -> GameSessionAction {
+        start_game_with(1, false)
+    }
+
+    fn start_game_with(boards: u8, hard_mode: bool) -> GameSessionAction {
+        GameSessionAction::StartGame {
+            language: "en".to_string(),
+            length: 5,
+            hard_mode,
+            mode: GameMode::Classic,
+            boards,
+            category: None,
+        }
+    }
+
+    fn check_word(word: &str) -> GameSessionAction {
+        GameSessionAction::CheckWord { word: word.to_string() }
+    }
+
+    fn session_info(game_session: &Program) -> SessionInfo {
+        let state: GameSessionState = game_session.read_state(()).unwrap();
+        state
+            .game_sessions
+            .into_iter()
+            .find(|(user, _)| *user == USER1.into())
+            .unwrap()
+            .1
+    }
+
     #[test]
     fn test_start_game() {
         let sys = setup();
         let game_session = sys.get_program(GAME_SESSION_ID).unwrap();
 
         // Simulate user sending StartGame request
-        assert!(!game_session.send(USER1, GameSessionAction::StartGame).main_failed());
+        assert!(!game_session.send(USER1, start_game()).main_failed());
 
         // Check if the game session has been created
         let state: GameSessionState = game_session.read_state(()).unwrap();
@@ -58,10 +100,17 @@ mod tests {
         let game_session = sys.get_program(GAME_SESSION_ID).unwrap();
 
         // Start the game
-        assert!(!game_session.send(USER1, GameSessionAction::StartGame).main_failed());
+        assert!(!game_session.send(USER1, start_game()).main_failed());
 
-        // Simulate a correct word check
+        // A wrong guess counts as a try
         assert!(!game_session.send(USER1, GameSessionAction::CheckWord { word: "hello".to_string() }).main_failed());
+        let state: GameSessionState = game_session.read_state(()).unwrap();
+        let session_info = &state.game_sessions[0].1;
+        assert_eq!(session_info.tries, 1);
+        assert!(matches!(session_info.session_status, SessionStatus::WaitUserInput));
+
+        // Simulate a correct word check
+        assert!(!game_session.send(USER1, GameSessionAction::CheckWord { word: "house".to_string() }).main_failed());
 
         // Validate the session status and tries count
         let state: GameSessionState = game_session.read_state(()).unwrap();
@@ -73,8 +122,73 @@ mod tests {
             .1.clone();
         println!("State after CheckWord: {:?}", state);
 
-        assert_eq!(session_info.tries, 0);  // Adjust based on expected behavior
-        assert!(matches!(session_info.session_status, SessionStatus::ReplyReceived(_)));
+        assert_eq!(session_info.tries, 2);
+        assert!(matches!(session_info.session_status, SessionStatus::GameOver(GameStatus::Win)));
+    }
+
+    #[test]
+    fn test_multi_board_win() {
+        let sys = setup_with_words(&["house", "mouse"]);
+        let game_session = sys.get_program(GAME_SESSION_ID).unwrap();
+        assert!(!game_session.send(USER1, start_game_with(2, false)).main_failed());
+
+        // Solving one board isn't enough
+        let res = game_session.send(USER1, check_word("house"));
+        assert!(res.log().iter().any(|log| matches!(
+            GameSessionEvent::decode(&mut log.payload()),
+            Ok(GameSessionEvent::BoardsCheckResult { feedback }) if feedback.len() == 2
+        )));
+        let session = session_info(&game_session);
+        assert_eq!((session.tries, session.solved_boards), (1, 1));
+        assert!(matches!(session.session_status, SessionStatus::WaitUserInput));
+
+        let res = game_session.send(USER1, check_word("mouse"));
+        assert!(res.contains(&Log::builder().dest(USER1).payload(GameSessionEvent::GameOver(GameStatus::Win))));
+        let session = session_info(&game_session);
+        assert_eq!((session.tries, session.solved_boards), (2, 2));
+    }
+
+    #[test]
+    fn test_multi_board_loss() {
+        let sys = setup_with_words(&["house", "mouse"]);
+        let game_session = sys.get_program(GAME_SESSION_ID).unwrap();
+        assert!(!game_session.send(USER1, start_game_with(2, false)).main_failed());
+
+        // Two boards get seven tries, one more than a single board
+        for _ in 0..6 {
+            assert!(!game_session.send(USER1, check_word("wrong")).main_failed());
+        }
+        assert!(matches!(session_info(&game_session).session_status, SessionStatus::WaitUserInput));
+
+        let res = game_session.send(USER1, check_word("wrong"));
+        assert!(res.contains(&Log::builder().dest(USER1).payload(GameSessionEvent::GameOver(GameStatus::Lose))));
+        assert_eq!(session_info(&game_session).tries as u32, max_tries(2));
+    }
+
+    #[test]
+    fn test_rejected_guesses_are_not_tries() {
+        let sys = setup();
+        let game_session = sys.get_program(GAME_SESSION_ID).unwrap();
+        assert!(!game_session.send(USER1, start_game_with(1, true)).main_failed());
+
+        let res = game_session.send(USER1, check_word("zzzzz"));
+        assert!(res.contains(&Log::builder().dest(USER1).payload(GameSessionEvent::InvalidWord)));
+        assert_eq!(session_info(&game_session).tries, 0);
+
+        // "hello" reveals the first letter, which hard mode keeps in place
+        assert!(!game_session.send(USER1, check_word("hello")).main_failed());
+        let res = game_session.send(USER1, check_word("wrong"));
+        let violation = HardModeViolation::CorrectLetterMoved {
+            position: 0,
+            letter: "h".to_string(),
+        };
+        assert!(res.contains(&Log::builder().dest(USER1).payload(GameSessionEvent::HardModeViolation(violation))));
+        let session = session_info(&game_session);
+        assert_eq!(session.tries, 1);
+        assert!(matches!(session.session_status, SessionStatus::WaitUserInput));
+
+        assert!(!game_session.send(USER1, check_word("house")).main_failed());
+        assert!(matches!(session_info(&game_session).session_status, SessionStatus::GameOver(GameStatus::Win)));
     }
 
     #[test]
@@ -83,7 +197,7 @@ mod tests {
         let game_session = sys.get_program(GAME_SESSION_ID).unwrap();
 
         // Start the game
-        assert!(!game_session.send(USER1, GameSessionAction::StartGame).main_failed());
+        assert!(!game_session.send(USER1, start_game()).main_failed());
 
         // Simulate several incorrect word checks until the game is over
         for _ in 0..6 {
@@ -113,7 +227,7 @@ fn test_timeout_with_user_action() {
     let game_session = sys.get_program(GAME_SESSION_ID).unwrap();
 
     // Start the game
-    assert!(!game_session.send(USER1, GameSessionAction::StartGame).main_failed());
+    assert!(!game_session.send(USER1, start_game()).main_failed());
 
     // Simulate a delay but before timeout, user checks the word
     sys.spend_blocks(15);
@@ -134,4 +248,35 @@ fn test_timeout_with_user_action() {
 
     assert!(matches!(session_info.session_status, SessionStatus::GameOver(_)));
 }
+
+#[test]
+fn test_timeout_ends_wordle_game() {
+    let sys = setup();
+    let wordle = sys.get_program(WORDLE_ID).unwrap();
+    let game_session = sys.get_program(GAME_SESSION_ID).unwrap();
+
+    assert!(!game_session.send(USER1, start_game()).main_failed());
+    let reply: StateReply = wordle.read_state(StateQuery::ActiveGames).unwrap();
+    assert!(matches!(reply, StateReply::ActiveGames(1)));
+
+    // Nobody else can time out the game
+    let state: GameSessionState = game_session.read_state(()).unwrap();
+    let session_id = state.game_sessions[0].1.session_id;
+    let check_status = GameSessionAction::CheckGameStatus { user: USER1.into(), session_id };
+    assert!(game_session.send(USER1, check_status).main_failed());
+
+    // The unfinished game is lost and the Wordle game is ended with it
+    sys.spend_blocks(201);
+    let state: GameSessionState = game_session.read_state(()).unwrap();
+    assert!(matches!(state.game_sessions[0].1.session_status, SessionStatus::GameOver(GameStatus::Lose)));
+    let reply: StateReply = wordle.read_state(StateQuery::ActiveGames).unwrap();
+    assert!(matches!(reply, StateReply::ActiveGames(0)));
+
+    // Guesses get the result, and a new game can be started
+    let res = game_session.send(USER1, GameSessionAction::CheckWord { word: "house".to_string() });
+    assert!(res.contains(&Log::builder().dest(USER1).payload(GameSessionEvent::GameOver(GameStatus::Lose))));
+    assert!(!game_session.send(USER1, start_game()).main_failed());
+    let reply: StateReply = wordle.read_state(StateQuery::ActiveGames).unwrap();
+    assert!(matches!(reply, StateReply::ActiveGames(1)));
+}
 }
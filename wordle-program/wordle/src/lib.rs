@@ -1,24 +1,408 @@
 #![no_std]
 
-use gstd::{collections::HashMap, debug, exec, msg, string::String, vec::Vec, ActorId, ToString};
-use wordle_io::{Action, Event};
+use blake2::{digest::consts::U32, Blake2b, Digest};
+use gstd::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    debug, exec, msg,
+    rc::Rc,
+    string::String,
+    vec::Vec,
+    ActorId, Encode,
+};
+use wordle_io::{
+    assert_valid_category, assert_valid_language, assert_valid_word, check_letters,
+    dictionary_hash, filter_candidates, max_tries, narrow_candidates, normalize_word,
+    suggest_guess, word_commitment, word_length, Action, BoardFeedback, DailyConfig, DailyResult,
+    DictionaryInfo, Event, Feedback, GameInfo, GameMode, GameResult, HardModeConstraints,
+    IndexSet, InitConfig, Letters, PackedWords, StateQuery, StateReply, UserStats, WordList,
+    MAX_BOARDS, MAX_HINTS, WORD_LENGTHS,
+};
 
 static mut WORDLE: Option<Wordle> = None;
-const BANK_OR_WORDS: [&str; 1] = ["house"];
-const WORD_LENGTH: usize = 5;
 
 #[derive(Default)]
 struct Wordle {
-    games: HashMap<ActorId, String>,
+    games: HashMap<ActorId, Game>,
+    stats: HashMap<ActorId, UserStats>,
+    // 每个用户在每种语言、长度和分类下已经猜过的答案，语言和分类都用下标表示
+    seen: HashMap<ActorId, BTreeMap<(u16, u8, Option<u16>), SeenAnswers>>,
+    // 每种语言各自的词库
+    banks: BTreeMap<String, WordBank>,
+    // 按创建顺序排列的语言，游戏等只保存其下标
+    languages: Vec<String>,
+    // 未配置时不能玩每日单词
+    daily: Option<Daily>,
+    // 可以代替任意用户操作的程序，例如 game-session
+    trusted_callers: BTreeSet<ActorId>,
+    owner: ActorId,
+}
+
+struct Daily {
+    config: DailyConfig,
+    // 当前期数，以及本期已经玩过每日单词的玩家
+    epoch: u32,
+    played: BTreeSet<(ActorId, u16, u8)>,
+    // 本期第一次开始每日单词时生成的随机种子，在此之前无法算出本期的单词
+    seed: Option<[u8; 32]>,
+    // 本期每种语言和长度选定的单词，固定在第一次开始时的分组上，期间重新封存词典也不会改变
+    words: BTreeMap<(u16, u8), DailyWord>,
+    // 每期解出每日单词的玩家，尝试次数少的在前
+    leaderboards: BTreeMap<u32, Vec<DailyResult>>,
+}
+
+#[derive(Default)]
+struct WordBank {
+    // 词库的语言在 languages 中的下标
+    language: u16,
+    // 新游戏使用最近一次封存的词典
+    dictionary: Dictionary,
+    // 所有者正在编辑的词表，封存后成为下一个版本
+    draft: BTreeSet<String>,
+    // 草稿中的分类，只包含草稿里的单词，封存时随词典一起生效
+    categories: BTreeMap<String, BTreeSet<String>>,
+    // 创建过的分类名，游戏只保存其下标。分类删除后下标保留，重新创建时沿用
+    category_names: Vec<String>,
+    // 可以用来猜测但不会被选为答案的单词，按长度分组
+    allowed_guesses: BTreeMap<u8, PackedWords>,
+}
+
+// 只对同一个词典版本有效，版本变化或全部猜过后重新开始
+#[derive(Default)]
+struct SeenAnswers {
+    dictionary_version: u32,
+    indexes: IndexSet,
+}
+
+#[derive(Default)]
+struct Dictionary {
+    version: u32,
+    // 按单词长度分组，每组内有序并紧凑存储。游戏共享开始时的分组
+    buckets: BTreeMap<u8, Rc<PackedWords>>,
+    // 每个分类同样按长度分组
+    categories: BTreeMap<String, BTreeMap<u8, Rc<PackedWords>>>,
+    size: u32,
+    hash: [u8; 32],
+}
+
+struct Game {
+    // 每个面板各有一个待猜的单词，普通模式之外只有一个面板
+    boards: Vec<Board>,
+    // 开始时的分组，答案和提示都从中选取，词典重新封存后下标依然有效
+    words: Rc<PackedWords>,
+    // 计入尝试次数的猜测，用来推算与所有反馈一致的候选词，顺序和重复不影响结果
+    guesses: PackedWords,
+    hints: u32,
+    mode: GameMode,
+    length: u8,
+    // 语言在 languages 中的下标，分类在词库 category_names 中的下标
+    language: u16,
+    category: Option<u16>,
+    dictionary_version: u32,
+    // 困难模式下累积的提示约束，普通模式为 None
+    hard_mode: Option<HardModeConstraints>,
+    // 每日单词所属的期数，解出后清空
+    daily: Option<u32>,
+    tries: u32,
+}
+
+struct DailyWord {
+    words: Rc<PackedWords>,
+    index: u32,
+    dictionary_version: u32,
+}
+
+struct Board {
+    secret: Secret,
+    solved: bool,
+}
+
+enum Secret {
+    // 开始时选定的单词在分组中的下标，游戏开始时公布 hash(word || salt)，结束时揭晓
+    Word { index: u32, salt: [u8; 32] },
+    // Absurdle 模式不预先选词，只记录仍可能是答案的单词在分组中的下标
+    Candidates { indexes: IndexSet },
+}
+
+impl Game {
+    fn commitments(&self) -> Vec<[u8; 32]> {
+        self.boards
+            .iter()
+            .filter_map(|board| board.secret.commitment(&self.words))
+            .collect()
+    }
+}
+
+impl Secret {
+    fn commitment(&self, words: &PackedWords) -> Option<[u8; 32]> {
+        match self {
+            Secret::Word { index, salt } => {
+                Some(word_commitment(&words.get(*index as usize), salt))
+            }
+            Secret::Candidates { .. } => None,
+        }
+    }
+
+    // 普通模式直接比较定长的字母数组，不分配内存
+    fn check(&mut self, words: &PackedWords, guess: &str) -> Feedback {
+        match self {
+            Secret::Word { index, .. } => {
+                check_letters(&words.letters(*index as usize), &Letters::new(guess))
+            }
+            Secret::Candidates { indexes } => narrow_candidates(words, indexes, guess),
+        }
+    }
+
+    // 分组中与目前所有反馈一致的单词
+    fn candidates(&self, words: &PackedWords, guesses: &PackedWords) -> IndexSet {
+        match self {
+            Secret::Word { index, .. } => {
+                let answer = words.letters(*index as usize);
+                let mut candidates = IndexSet::with_all(words.len());
+                for guess in (0..guesses.len()).map(|i| guesses.get(i)) {
+                    let feedback = check_letters(&answer, &Letters::new(&guess));
+                    filter_candidates(words, &mut candidates, &guess, &feedback);
+                }
+                candidates
+            }
+            Secret::Candidates { indexes } => indexes.clone(),
+        }
+    }
+
+    fn reveal(self, words: &PackedWords) -> (String, [u8; 32]) {
+        match self {
+            Secret::Word { index, salt } => (words.get(index as usize), salt),
+            Secret::Candidates { indexes } => {
+                let index = indexes.iter().next().expect("No candidates left");
+                (words.get(index), [0; 32])
+            }
+        }
+    }
+}
+
+impl Wordle {
+    fn assert_owner(&self) {
+        assert_eq!(msg::source(), self.owner, "Only the owner can manage the dictionary");
+    }
+
+    // 用户只能为自己操作，受信任的程序可以代替任意用户
+    fn assert_can_act_for(&self, user: &ActorId) {
+        let source = msg::source();
+        assert!(
+            source == *user || self.trusted_callers.contains(&source),
+            "Not allowed to act for this user"
+        );
+    }
+
+    // 结束游戏并释放其内存，记录结果，并向玩家揭晓单词和 salt，供其验证开始时公布的承诺
+    fn finish_game(&mut self, user: ActorId, result: GameResult) {
+        let game = self.games.remove(&user).expect("There is no game with this user");
+        let stats = self.stats.entry(user).or_default();
+        match result {
+            GameResult::Won => stats.wins += 1,
+            GameResult::Lost => stats.losses += 1,
+        }
+        // 用过提示的胜利记为辅助胜利
+        if result == GameResult::Won && game.hints > 0 {
+            stats.assisted_wins += 1;
+        }
+        for (board, Board { secret, .. }) in game.boards.into_iter().enumerate() {
+            let (word, salt) = secret.reveal(&game.words);
+            let revealed = Event::GameRevealed {
+                user,
+                board: board as u8,
+                word,
+                salt,
+            };
+            msg::send(user, revealed, 0).expect("Error in sending the revealed word");
+        }
+    }
+
+    // 没有该语言的词库时新建一个，并为语言分配下标
+    fn bank_mut(&mut self, language: String) -> &mut WordBank {
+        assert_valid_language(&language);
+        let languages = &mut self.languages;
+        self.banks.entry(language).or_insert_with_key(|language| {
+            languages.push(language.clone());
+            WordBank {
+                language: (languages.len() - 1) as u16,
+                ..Default::default()
+            }
+        })
+    }
+
+    // 词库不会被删除，游戏的语言下标总能找到词库
+    fn game_bank(&self, game: &Game) -> (&String, &WordBank) {
+        let language = &self.languages[game.language as usize];
+        (language, &self.banks[language])
+    }
+
+    fn is_real_word(&self, game: &Game, word: &str) -> bool {
+        let length = word_length(word);
+        if length != game.length {
+            return false;
+        }
+        let (_, bank) = self.game_bank(game);
+        game.words.contains(word)
+            || bank.allowed_guesses.get(&length).is_some_and(|words| words.contains(word))
+            || bank
+                .dictionary
+                .buckets
+                .get(&length)
+                .is_some_and(|words| words.contains(word))
+    }
+}
+
+impl Daily {
+    // 进入新的一期时清空已玩过的玩家和选定的单词并生成新的种子，每个玩家每期只能玩一次。
+    // 同一期、同一语言和长度的每日单词对所有玩家都相同
+    fn start(
+        &mut self,
+        user: ActorId,
+        language: u16,
+        length: u8,
+        words: &Rc<PackedWords>,
+        dictionary_version: u32,
+    ) -> (u32, &DailyWord) {
+        let epoch = exec::block_height() / self.config.epoch_length;
+        if epoch != self.epoch || self.seed.is_none() {
+            let (seed, _) = exec::random(random_subject(epoch)).expect("Error in getting random number");
+            self.epoch = epoch;
+            self.seed = Some(seed);
+            self.played.clear();
+            self.words.clear();
+        }
+        assert!(
+            self.played.insert((user, language, length)),
+            "The daily word can be played once per epoch"
+        );
+        let seed = self.seed;
+        let word = self.words.entry((language, length)).or_insert_with(|| {
+            let digest: [u8; 32] = Blake2b::<U32>::digest((seed, language, length).encode()).into();
+            let mut bytes = [0; 8];
+            bytes.copy_from_slice(&digest[..8]);
+            DailyWord {
+                words: words.clone(),
+                index: (u64::from_le_bytes(bytes) % words.len() as u64) as u32,
+                dictionary_version,
+            }
+        });
+        (epoch, word)
+    }
+
+    fn record(&mut self, epoch: u32, result: DailyResult) {
+        let results = self.leaderboards.entry(epoch).or_default();
+        let at = results.partition_point(|r| r.tries <= result.tries);
+        results.insert(at, result);
+    }
+}
+
+// 按单词长度分组，单词有序，每次都追加在分组末尾
+fn buckets<'a>(words: impl IntoIterator<Item = &'a String>) -> BTreeMap<u8, Rc<PackedWords>> {
+    let mut buckets: BTreeMap<u8, PackedWords> = BTreeMap::new();
+    for word in words {
+        let length = word_length(word);
+        buckets
+            .entry(length)
+            .or_insert_with(|| PackedWords::new(length))
+            .insert(word);
+    }
+    buckets
+        .into_iter()
+        .map(|(length, words)| (length, Rc::new(words)))
+        .collect()
+}
+
+impl WordBank {
+    fn seal_dictionary(&mut self) -> &Dictionary {
+        assert!(!self.draft.is_empty(), "The dictionary is empty");
+        let words: Vec<String> = self.draft.iter().cloned().collect();
+        let categories = self
+            .categories
+            .iter()
+            .map(|(category, words)| (category.clone(), buckets(words)))
+            .collect();
+        self.dictionary = Dictionary {
+            version: self.dictionary.version + 1,
+            buckets: buckets(&words),
+            categories,
+            size: words.len() as u32,
+            hash: dictionary_hash(&words),
+        };
+        &self.dictionary
+    }
+
+    fn category_mut(&mut self, category: &str) -> &mut BTreeSet<String> {
+        self.categories.get_mut(category).expect("There is no such category")
+    }
+
+    fn category_index(&self, category: &str) -> Option<u16> {
+        self.category_names.iter().position(|name| name == category).map(|id| id as u16)
+    }
+
+    // 分类名第一次出现时分配下标
+    fn category_id(&mut self, category: &str) -> u16 {
+        self.category_index(category).unwrap_or_else(|| {
+            self.category_names.push(category.into());
+            (self.category_names.len() - 1) as u16
+        })
+    }
+
+    // 从草稿中删除单词时也将其移出所有分类
+    fn remove_word(&mut self, word: &str) {
+        self.draft.remove(word);
+        for words in self.categories.values_mut() {
+            words.remove(word);
+        }
+    }
+
+    fn add_allowed_guess(&mut self, word: &str) {
+        let length = word_length(word);
+        self.allowed_guesses
+            .entry(length)
+            .or_insert_with(|| PackedWords::new(length))
+            .insert(word);
+    }
+
+    fn remove_allowed_guess(&mut self, word: &str) {
+        if let Some(words) = self.allowed_guesses.get_mut(&word_length(word)) {
+            words.remove(word);
+        }
+    }
+
+    fn allowed_guesses_count(&self) -> u32 {
+        self.allowed_guesses.values().map(|words| words.len() as u32).sum()
+    }
 }
 
 #[no_mangle]
 extern "C" fn init() {
     debug!("开始初始化 Wordle 合约");
+    let config: InitConfig = msg::load().expect("Unable to decode InitConfig");
+    config.assert_valid();
+    let mut wordle = Wordle {
+        daily: config.daily.map(|config| Daily {
+            config,
+            epoch: 0,
+            played: BTreeSet::new(),
+            seed: None,
+            words: BTreeMap::new(),
+            leaderboards: BTreeMap::new(),
+        }),
+        trusted_callers: config.trusted_callers.into_iter().collect(),
+        owner: config.owner,
+        ..Default::default()
+    };
+    let bank = wordle.bank_mut(config.language);
+    bank.draft = config.words.iter().map(|word| normalize_word(word)).collect();
+    for word in &config.allowed_guesses {
+        bank.add_allowed_guess(&normalize_word(word));
+    }
+    // 初始词表不为空时直接封存为第一个版本
+    if !bank.draft.is_empty() {
+        bank.seal_dictionary();
+    }
     unsafe {
-        WORDLE = Some(Wordle {
-            games: HashMap::new(),
-        });
+        WORDLE = Some(wordle);
     }
     debug!("初始化 Wordle 合约完成");
 }
@@ -30,57 +414,460 @@ extern "C" fn handle() {
         unsafe { WORDLE.as_mut().expect("Wordle program is not initialized") };
 
     let reply = match action {
-        Action::StartGame { user } => {
-            // 随机抽取一个单词，并将用户id 与 单词一起存入 games
+        Action::StartGame {
+            user,
+            language,
+            length,
+            hard_mode,
+            mode,
+            boards,
+            category,
+            force,
+        } => {
+            wordle.assert_can_act_for(&user);
+            assert!((1..=MAX_BOARDS).contains(&boards), "Unsupported number of boards");
+            // 多面板只支持普通模式，每日单词和 Absurdle 都只有一个答案
+            assert!(
+                boards == 1 || (mode == GameMode::Classic && !hard_mode),
+                "Several boards are only supported in classic games"
+            );
+            // 每日单词对所有玩家相同，不能按分类选择
+            assert!(
+                category.is_none() || mode != GameMode::Daily,
+                "The daily word can't be picked from a category"
+            );
+            // 未结束的游戏不会被悄悄覆盖，强制开始时按放弃处理
+            if wordle.games.contains_key(&user) {
+                if !force {
+                    msg::reply(Event::GameInProgress { user }, 0).expect("Error in sending a reply");
+                    return;
+                }
+                wordle.finish_game(user, GameResult::Lost);
+            }
+            // 从对应长度的分组中随机抽取一个单词，并将用户id 与 单词一起存入 games
             // 即这个单词是该用户要猜的单词
-            let random_id = get_random_value(BANK_OR_WORDS.len() as u8);
-            let word = BANK_OR_WORDS[random_id as usize]; // const 变量不需要使用 unsafe
-            wordle.games.insert(user, word.to_string()); // 这里的 to_string() 方法，要先引入 gstd::ToString，不是 gstd::string::ToString
-            Event::GameStarted { user }
+            assert!(WORD_LENGTHS.contains(&length), "Unsupported word length");
+            let bank = wordle
+                .banks
+                .get(&language)
+                .expect("There is no word bank for this language");
+            let dictionary = &bank.dictionary;
+            assert!(dictionary.size > 0, "The dictionary is not sealed yet");
+            let buckets = match &category {
+                Some(category) => dictionary
+                    .categories
+                    .get(category)
+                    .expect("There is no such category"),
+                None => &dictionary.buckets,
+            };
+            // 封存的分类一定创建过，已经分配了下标
+            let category = category.map(|category| {
+                bank.category_index(&category).expect("There is no such category")
+            });
+            let mut words = buckets.get(&length).expect("There are no words of this length").clone();
+            assert!(words.len() >= boards as usize, "Not enough words for this many boards");
+            let mut dictionary_version = dictionary.version;
+            let mut daily_epoch = None;
+            // 每日单词使用本期选定的分组和下标
+            let mut daily_index = None;
+            if mode == GameMode::Daily {
+                let daily = wordle.daily.as_mut().expect("The daily challenge is disabled");
+                let (epoch, word) = daily.start(user, bank.language, length, &words, dictionary.version);
+                words = word.words.clone();
+                dictionary_version = word.dictionary_version;
+                daily_index = Some(word.index as usize);
+                daily_epoch = Some(epoch);
+            }
+            let boards = if mode == GameMode::Absurdle {
+                let secret = Secret::Candidates {
+                    indexes: IndexSet::with_all(words.len()),
+                };
+                Vec::from([Board { secret, solved: false }])
+            } else {
+                let seen = wordle
+                    .seen
+                    .entry(user)
+                    .or_default()
+                    .entry((bank.language, length, category))
+                    .or_default();
+                // 没猜过的单词不够所有面板使用时重新开始
+                if seen.dictionary_version != dictionary.version
+                    || seen.indexes.len() + boards as usize > words.len()
+                {
+                    seen.dictionary_version = dictionary.version;
+                    seen.indexes.clear();
+                }
+                (0..boards)
+                    .map(|board| {
+                        // 普通模式只在用户没猜过的单词中抽取，各面板的单词互不相同
+                        let random_id = match daily_index {
+                            Some(index) => index,
+                            None => {
+                                let unseen = words.len() - seen.indexes.len();
+                                seen.indexes.nth_absent(random_index((user, board), unseen))
+                            }
+                        };
+                        // 本期的每日单词可能来自之前的词典版本，下标不能记入当前版本
+                        if dictionary_version == seen.dictionary_version {
+                            seen.indexes.insert(random_id);
+                        }
+                        let (salt, _) = exec::random(random_subject((user, board)))
+                            .expect("Error in getting random number");
+                        let secret = Secret::Word {
+                            index: random_id as u32,
+                            salt,
+                        };
+                        Board { secret, solved: false }
+                    })
+                    .collect()
+            };
+            let game = Game {
+                boards,
+                words,
+                guesses: PackedWords::new(length),
+                hints: 0,
+                mode,
+                length,
+                dictionary_version,
+                language: bank.language,
+                category,
+                hard_mode: hard_mode.then(HardModeConstraints::default),
+                daily: daily_epoch,
+                tries: 0,
+            };
+            debug!(
+                "用户 {:?} 使用 {} 词典版本 {}，单词长度 {}",
+                user, language, game.dictionary_version, length
+            );
+            let commitments = game.commitments();
+            wordle.games.insert(user, game);
+            wordle.stats.entry(user).or_default().games_played += 1;
+            Event::GameStarted { user, commitments }
         }
         Action::CheckWord { user, word } => {
-            if word.len() != WORD_LENGTH {
-                panic!("The length of the word must be {}", WORD_LENGTH);
-            }
+            wordle.assert_can_act_for(&user);
             // 取出该用户要猜的单词
-            let key_word = wordle
+            let game = wordle
                 .games
                 .get(&user)
                 .expect("There is no game with this user");
-            let mut matched_indices = Vec::with_capacity(WORD_LENGTH);
-            let mut key_indices = Vec::with_capacity(WORD_LENGTH);
-
+            // 大小写和 Unicode 形式不同的输入视为同一个单词
+            let word = normalize_word(&word);
+            // 不存在的单词不消耗尝试次数
+            if !wordle.is_real_word(game, &word) {
+                msg::reply(Event::InvalidWord { user, word }, 0).expect("Error in sending a reply");
+                return;
+            }
+            // 困难模式下违反已揭示提示的单词同样不消耗尝试次数
+            if let Some(Err(violation)) = game.hard_mode.as_ref().map(|c| c.check(&word)) {
+                msg::reply(Event::HardModeViolation { user, violation }, 0)
+                    .expect("Error in sending a reply");
+                return;
+            }
             // 比较待猜的单词，和用户输入的单词
-            for (i, (a, b)) in key_word.chars().zip(word.chars()).enumerate() {
-                if a == b {
-                    // 如果同一索引位置，字符相同，则将索引存入 matched_indices
-                    matched_indices.push(i as u8);
-                } else if key_word.contains(b) {
-                    // 如果字符不同，但是待猜的单词包含用户输入的字符，
-                    // 则将索引存入 key_indices
-                    key_indices.push(i as u8);
+            // 已经解出的面板不再给出提示
+            let game = wordle.games.get_mut(&user).expect("There is no game with this user");
+            let single_board = game.boards.len() == 1;
+            let mut feedback = Vec::new();
+            for (index, board) in game.boards.iter_mut().enumerate() {
+                if board.solved {
+                    continue;
                 }
+                let board_feedback = board.secret.check(&game.words, &word);
+                board.solved = board_feedback.is_solved();
+                feedback.push(BoardFeedback {
+                    board: index as u8,
+                    feedback: board_feedback,
+                });
             }
-            Event::WordChecked {
-                user,
-                correct_position: matched_indices,
-                contained_in_word: key_indices,
+            game.tries += 1;
+            game.guesses.insert(&word);
+            // 困难模式只有一个面板
+            if let Some(constraints) = game.hard_mode.as_mut() {
+                constraints.update(&word, &feedback[0].feedback);
+            }
+            // 解出每日单词的玩家进入当期排行榜
+            if game.boards.iter().all(|board| board.solved) {
+                if let (Some(epoch), Some(daily)) = (game.daily, wordle.daily.as_mut()) {
+                    let result = DailyResult {
+                        user,
+                        language: wordle.languages[game.language as usize].clone(),
+                        length: game.length,
+                        tries: game.tries,
+                        hints: game.hints,
+                    };
+                    daily.record(epoch, result);
+                }
+                wordle.finish_game(user, GameResult::Won);
+            } else if game.tries >= max_tries(game.boards.len() as u8) {
+                wordle.finish_game(user, GameResult::Lost);
+            }
+            if single_board {
+                Event::WordChecked {
+                    user,
+                    feedback: feedback[0].feedback,
+                }
+            } else {
+                Event::BoardsChecked { user, feedback }
             }
         },
+        Action::AddWords { language, words } => {
+            wordle.assert_owner();
+            let bank = wordle.bank_mut(language);
+            for word in &words {
+                let word = normalize_word(word);
+                assert_valid_word(&word);
+                bank.draft.insert(word);
+            }
+            Event::DictionaryUpdated {
+                draft_size: bank.draft.len() as u32,
+            }
+        }
+        Action::RemoveWords { language, words } => {
+            wordle.assert_owner();
+            let bank = wordle.bank_mut(language);
+            for word in &words {
+                bank.remove_word(&normalize_word(word));
+            }
+            Event::DictionaryUpdated {
+                draft_size: bank.draft.len() as u32,
+            }
+        }
+        Action::SealDictionary { language } => {
+            wordle.assert_owner();
+            let dictionary = wordle.bank_mut(language.clone()).seal_dictionary();
+            Event::DictionarySealed {
+                language,
+                version: dictionary.version,
+                hash: dictionary.hash,
+                size: dictionary.size,
+            }
+        }
+        Action::CreateCategory { language, category } => {
+            wordle.assert_owner();
+            assert_valid_category(&category);
+            let bank = wordle.bank_mut(language.clone());
+            bank.category_id(&category);
+            let size = bank.categories.entry(category.clone()).or_default().len() as u32;
+            Event::CategoryUpdated {
+                language,
+                category,
+                size: Some(size),
+            }
+        }
+        Action::RemoveCategory { language, category } => {
+            wordle.assert_owner();
+            let bank = wordle.bank_mut(language.clone());
+            assert!(bank.categories.remove(&category).is_some(), "There is no such category");
+            Event::CategoryUpdated {
+                language,
+                category,
+                size: None,
+            }
+        }
+        Action::AddToCategory {
+            language,
+            category,
+            words,
+        } => {
+            wordle.assert_owner();
+            let words: Vec<String> = words.iter().map(|word| normalize_word(word)).collect();
+            for word in &words {
+                assert_valid_word(word);
+            }
+            let bank = wordle.bank_mut(language.clone());
+            let category_words = bank.category_mut(&category);
+            category_words.extend(words.iter().cloned());
+            let size = category_words.len() as u32;
+            bank.draft.extend(words);
+            Event::CategoryUpdated {
+                language,
+                category,
+                size: Some(size),
+            }
+        }
+        Action::RemoveFromCategory {
+            language,
+            category,
+            words,
+        } => {
+            wordle.assert_owner();
+            let bank = wordle.bank_mut(language.clone());
+            let category_words = bank.category_mut(&category);
+            for word in &words {
+                category_words.remove(&normalize_word(word));
+            }
+            Event::CategoryUpdated {
+                language,
+                category,
+                size: Some(category_words.len() as u32),
+            }
+        }
+        Action::AddAllowedGuesses { language, words } => {
+            wordle.assert_owner();
+            let bank = wordle.bank_mut(language);
+            for word in &words {
+                let word = normalize_word(word);
+                assert_valid_word(&word);
+                bank.add_allowed_guess(&word);
+            }
+            Event::AllowedGuessesUpdated {
+                size: bank.allowed_guesses_count(),
+            }
+        }
+        Action::RemoveAllowedGuesses { language, words } => {
+            wordle.assert_owner();
+            let bank = wordle.bank_mut(language);
+            for word in &words {
+                bank.remove_allowed_guess(&normalize_word(word));
+            }
+            Event::AllowedGuessesUpdated {
+                size: bank.allowed_guesses_count(),
+            }
+        }
+        Action::DailyLeaderboard { epoch } => {
+            let daily = wordle.daily.as_ref().expect("The daily challenge is disabled");
+            Event::DailyLeaderboard {
+                epoch,
+                results: daily.leaderboards.get(&epoch).cloned().unwrap_or_default(),
+            }
+        }
+        Action::EndGame { user, result } => {
+            assert!(
+                wordle.trusted_callers.contains(&msg::source()),
+                "Only trusted callers can end a game"
+            );
+            wordle.finish_game(user, result);
+            Event::GameEnded { user, result }
+        }
+        Action::Abandon { user } => {
+            wordle.assert_can_act_for(&user);
+            wordle.finish_game(user, GameResult::Lost);
+            Event::GameEnded {
+                user,
+                result: GameResult::Lost,
+            }
+        }
+        Action::Hint { user } => {
+            wordle.assert_can_act_for(&user);
+            let game = wordle.games.get_mut(&user).expect("There is no game with this user");
+            assert!(game.hints < MAX_HINTS, "No hints left in this game");
+            // 提示第一个没有解出的面板，从与所有反馈一致的候选词中选取
+            let (board, secret) = game
+                .boards
+                .iter()
+                .enumerate()
+                .find_map(|(board, Board { secret, solved })| (!solved).then_some((board, secret)))
+                .expect("All boards are solved");
+            let candidates = secret.candidates(&game.words, &game.guesses);
+            let index = suggest_guess(&game.words, &candidates).expect("No candidates left");
+            game.hints += 1;
+            Event::Hint {
+                user,
+                board: board as u8,
+                word: game.words.get(index),
+                hints_left: MAX_HINTS - game.hints,
+            }
+        }
+        Action::AddTrustedCaller { program } => {
+            wordle.assert_owner();
+            wordle.trusted_callers.insert(program);
+            Event::TrustedCallersUpdated {
+                count: wordle.trusted_callers.len() as u32,
+            }
+        }
+        Action::RemoveTrustedCaller { program } => {
+            wordle.assert_owner();
+            wordle.trusted_callers.remove(&program);
+            Event::TrustedCallersUpdated {
+                count: wordle.trusted_callers.len() as u32,
+            }
+        }
     };
     msg::reply(reply, 0).expect("Error in sending a reply");
 }
 
-static mut SEED: u8 = 0;
-pub fn get_random_value(range: u8) -> u8 {
-    let seed = unsafe { SEED };
-    unsafe {
-        SEED = SEED.wrapping_add(1);
-    }
+#[no_mangle]
+extern "C" fn state() {
+    let query: StateQuery = msg::load().expect("Unable to load state query");
+    let wordle = unsafe { WORDLE.as_ref().expect("Wordle program is not initialized") };
+
+    let reply = match query {
+        StateQuery::ActiveGames => StateReply::ActiveGames(wordle.games.len() as u32),
+        StateQuery::Dictionary { language } => {
+            StateReply::Dictionary(wordle.banks.get(&language).map(|bank| DictionaryInfo {
+                version: bank.dictionary.version,
+                size: bank.dictionary.size,
+                hash: bank.dictionary.hash,
+                categories: bank
+                    .dictionary
+                    .categories
+                    .iter()
+                    .map(|(category, buckets)| {
+                        let size = buckets.values().map(|words| words.len() as u32).sum();
+                        (category.clone(), size)
+                    })
+                    .collect(),
+            }))
+        }
+        // 不公开待猜的单词
+        StateQuery::Game(user) => StateReply::Game(wordle.games.get(&user).map(|game| {
+            let (language, bank) = wordle.game_bank(game);
+            GameInfo {
+                language: language.clone(),
+                length: game.length,
+                category: game.category.map(|id| bank.category_names[id as usize].clone()),
+                dictionary_version: game.dictionary_version,
+                mode: game.mode,
+                hard_mode: game.hard_mode.is_some(),
+                daily_epoch: game.daily,
+                tries: game.tries,
+                hints: game.hints,
+                boards: game.boards.len() as u8,
+                solved_boards: game.boards.iter().filter(|board| board.solved).count() as u8,
+                commitments: game.commitments(),
+            }
+        })),
+        StateQuery::Stats(user) => {
+            StateReply::Stats(wordle.stats.get(&user).cloned().unwrap_or_default())
+        }
+        StateQuery::MemoryPages => StateReply::MemoryPages(memory_pages()),
+    };
+    msg::reply(reply, 0).expect("Failed to reply with state");
+}
+
+// 程序内存只会随分配增长，页数就是程序占用过的内存
+#[cfg(target_arch = "wasm32")]
+fn memory_pages() -> u32 {
+    core::arch::wasm32::memory_size::<0>() as u32
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn memory_pages() -> u32 {
+    0
+}
 
-    let mut random_input: [u8; 32] = exec::program_id().into();
-    random_input[0] = random_input[0].wrapping_add(seed);
+// 同一条消息中不同的种子得到不同的随机源
+fn random_subject(seed: impl Encode) -> [u8; 32] {
+    Blake2b::<U32>::digest((msg::id(), seed).encode()).into()
+}
 
-    let (random, _) = exec::random(random_input).expect("Error in getting random number");
-    random[0] % range
-}
\ No newline at end of file
+// 在 [0, len) 中均匀地随机取一个下标，随机源以消息 id 和种子（用户和面板）为种子。
+// 拒绝采样：丢弃落在最后一段不完整区间里的值，避免取模带来的偏差
+fn random_index(seed: impl Encode, len: usize) -> usize {
+    let len = len as u64;
+    let limit = u64::MAX / len * len;
+    let mut subject = random_subject(seed);
+    loop {
+        let (random, _) = exec::random(subject).expect("Error in getting random number");
+        for chunk in random.chunks_exact(8) {
+            let mut bytes = [0; 8];
+            bytes.copy_from_slice(chunk);
+            let value = u64::from_le_bytes(bytes);
+            if value < limit {
+                return (value % len) as usize;
+            }
+        }
+        subject = random;
+    }
+}
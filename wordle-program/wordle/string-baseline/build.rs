@@ -0,0 +1,5 @@
+use wordle_io::WordleMetadata;
+
+fn main() {
+    gear_wasm_builder::build_with_metadata::<WordleMetadata>()
+}
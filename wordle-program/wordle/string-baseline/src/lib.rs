@@ -0,0 +1,116 @@
+#![no_std]
+
+// 单词以 String 存储的 Wordle，只支持单面板的普通游戏，作为 benches 对比 gas 和内存的基准
+use core::ptr::addr_of_mut;
+use gstd::{
+    collections::{BTreeMap, HashMap},
+    exec, msg,
+    string::String,
+    vec::Vec,
+    ActorId,
+};
+use wordle_io::{
+    check_word, normalize_word, word_length, Action, Event, GameResult, InitConfig, StateQuery,
+    StateReply,
+};
+
+static mut WORDLE: Option<Wordle> = None;
+
+struct Wordle {
+    // 按单词长度分组，每组内有序，用二分查找检查猜测的单词
+    buckets: BTreeMap<u8, Vec<String>>,
+    // 每个游戏保存自己的单词
+    games: HashMap<ActorId, String>,
+}
+
+fn wordle() -> &'static mut Wordle {
+    unsafe { (*addr_of_mut!(WORDLE)).as_mut().expect("Wordle program is not initialized") }
+}
+
+#[no_mangle]
+extern "C" fn init() {
+    let config: InitConfig = msg::load().expect("Unable to decode InitConfig");
+    let mut buckets: BTreeMap<u8, Vec<String>> = BTreeMap::new();
+    for word in config.words {
+        let word = normalize_word(&word);
+        buckets.entry(word_length(&word)).or_default().push(word);
+    }
+    for words in buckets.values_mut() {
+        words.sort_unstable();
+    }
+    unsafe {
+        WORDLE = Some(Wordle {
+            buckets,
+            games: HashMap::new(),
+        });
+    }
+}
+
+#[no_mangle]
+extern "C" fn handle() {
+    let action: Action = msg::load().expect("Unable to decode action");
+    let wordle = wordle();
+
+    let reply = match action {
+        Action::StartGame { user, length, .. } => {
+            let words = wordle.buckets.get(&length).expect("There are no words of this length");
+            let (random, _) = exec::random(user.into()).expect("Error in getting random number");
+            let mut bytes = [0; 8];
+            bytes.copy_from_slice(&random[..8]);
+            let word = words[(u64::from_le_bytes(bytes) % words.len() as u64) as usize].clone();
+            wordle.games.insert(user, word);
+            Event::GameStarted {
+                user,
+                commitments: Vec::new(),
+            }
+        }
+        Action::CheckWord { user, word } => {
+            let answer = wordle.games.get(&user).expect("There is no game with this user");
+            let word = normalize_word(&word);
+            let known = wordle
+                .buckets
+                .get(&word_length(&word))
+                .is_some_and(|words| words.binary_search(&word).is_ok());
+            if !known {
+                Event::InvalidWord { user, word }
+            } else {
+                let feedback = check_word(answer, &word);
+                if feedback.is_solved() {
+                    wordle.games.remove(&user);
+                }
+                Event::WordChecked { user, feedback }
+            }
+        }
+        Action::Abandon { user } => {
+            wordle.games.remove(&user);
+            Event::GameEnded {
+                user,
+                result: GameResult::Lost,
+            }
+        }
+        _ => panic!("The baseline only plays classic games"),
+    };
+    msg::reply(reply, 0).expect("Error in sending a reply");
+}
+
+#[no_mangle]
+extern "C" fn state() {
+    let query: StateQuery = msg::load().expect("Unable to load state query");
+    let wordle = wordle();
+    let reply = match query {
+        StateQuery::ActiveGames => StateReply::ActiveGames(wordle.games.len() as u32),
+        StateQuery::MemoryPages => StateReply::MemoryPages(memory_pages()),
+        _ => panic!("The baseline only reports its games and memory"),
+    };
+    msg::reply(reply, 0).expect("Failed to reply with state");
+}
+
+#[cfg(target_arch = "wasm32")]
+fn memory_pages() -> u32 {
+    core::arch::wasm32::memory_size::<0>() as u32
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn memory_pages() -> u32 {
+    0
+}
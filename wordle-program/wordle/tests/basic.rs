@@ -0,0 +1,1077 @@
+#[cfg(test)]
+mod tests {
+    use gstd::{prelude::*, ActorId};
+    use gtest::{Log, Program, RunResult, System};
+    use wordle_io::*;
+
+    const USER1: u64 = 10;
+    const EN: &str = "en";
+
+    fn config(words: &[&str]) -> InitConfig {
+        InitConfig {
+            language: EN.to_string(),
+            words: words.iter().map(|word| word.to_string()).collect(),
+            owner: USER1.into(),
+            allowed_guesses: vec!["hello".to_string(), "world".to_string()],
+            daily: None,
+            trusted_callers: vec![],
+        }
+    }
+
+    fn start_game(user: ActorId, language: &str, length: u8) -> Action {
+        Action::StartGame {
+            user,
+            language: language.to_string(),
+            length,
+            hard_mode: false,
+            mode: GameMode::Classic,
+            boards: 1,
+            category: None,
+            force: false,
+        }
+    }
+
+    fn events(res: &RunResult) -> Vec<Event> {
+        res.log()
+            .iter()
+            .filter_map(|log| Event::decode(&mut log.payload()).ok())
+            .collect()
+    }
+
+    fn setup(sys: &System, words: &[&str]) -> Program<'_> {
+        let wordle = Program::current(sys);
+        assert!(!wordle.send(USER1, config(words)).main_failed());
+        wordle
+    }
+
+    #[test]
+    fn test_init_with_word_bank() {
+        let sys = System::new();
+        sys.init_logger();
+        let wordle = setup(&sys, &["house"]);
+        let user: ActorId = USER1.into();
+
+        let res = wordle.send(USER1, start_game(user, EN, 5));
+        let commitment = match events(&res)[..] {
+            [Event::GameStarted {
+                user: started,
+                ref commitments,
+            }] if started == user => commitments[0],
+            ref events => panic!("Unexpected events: {:?}", events),
+        };
+
+        let res = wordle.send(
+            USER1,
+            Action::CheckWord {
+                user,
+                word: "house".to_string(),
+            },
+        );
+        let checked = Event::WordChecked {
+            user,
+            feedback: Feedback::new(&[LetterResult::Correct; 5]),
+        };
+        assert!(res.contains(&Log::builder().dest(USER1).payload(checked)));
+
+        // The solved game reveals the word it committed to
+        let revealed = events(&res).into_iter().find_map(|event| match event {
+            Event::GameRevealed { word, salt, .. } => Some((word, salt)),
+            _ => None,
+        });
+        let (word, salt) = revealed.expect("The word is not revealed");
+        assert_eq!(word, "house");
+        assert!(verify_reveal(&commitment, &word, &salt));
+        assert!(!verify_reveal(&commitment, "mouse", &salt));
+    }
+
+    #[test]
+    fn test_lost_game_is_revealed() {
+        let sys = System::new();
+        sys.init_logger();
+        let wordle = setup(&sys, &["house"]);
+        let user: ActorId = USER1.into();
+
+        assert!(!wordle.send(USER1, start_game(user, EN, 5)).main_failed());
+        let check = || Action::CheckWord {
+            user,
+            word: "hello".to_string(),
+        };
+        for _ in 1..MAX_TRIES {
+            let res = wordle.send(USER1, check());
+            assert!(!events(&res).iter().any(|event| matches!(event, Event::GameRevealed { .. })));
+        }
+        let res = wordle.send(USER1, check());
+        assert!(events(&res).iter().any(|event| matches!(
+            event,
+            Event::GameRevealed { word, .. } if word == "house"
+        )));
+
+        // The game is over
+        assert!(wordle.send(USER1, check()).main_failed());
+    }
+
+    #[test]
+    fn test_invalid_word_banks() {
+        let sys = System::new();
+        sys.init_logger();
+
+        let banks: [&[&str]; 4] = [&["house", "abc"], &["House", "house"], &["house", "house"], &["hous3"]];
+        for words in banks {
+            let wordle = Program::current(&sys);
+            assert!(wordle.send(USER1, config(words)).main_failed());
+        }
+    }
+
+    #[test]
+    fn test_chunked_dictionary_upload() {
+        let sys = System::new();
+        sys.init_logger();
+        let wordle = setup(&sys, &[]);
+        let user: ActorId = USER1.into();
+
+        // Nothing to play until the owner seals a dictionary
+        assert!(wordle.send(USER1, start_game(user, EN, 5)).main_failed());
+
+        for chunk in [["house", "mouse"], ["horse", "louse"]] {
+            let words = chunk.iter().map(|word| word.to_string()).collect();
+            let language = EN.to_string();
+            assert!(!wordle.send(USER1, Action::AddWords { language, words }).main_failed());
+        }
+        let res = wordle.send(
+            USER1,
+            Action::RemoveWords {
+                language: EN.to_string(),
+                words: vec!["louse".to_string()],
+            },
+        );
+        assert!(res.contains(&Log::builder().dest(USER1).payload(Event::DictionaryUpdated { draft_size: 3 })));
+
+        let words: Vec<String> = ["horse", "house", "mouse"].iter().map(|word| word.to_string()).collect();
+        let res = wordle.send(USER1, Action::SealDictionary { language: EN.to_string() });
+        let sealed = Event::DictionarySealed {
+            language: EN.to_string(),
+            version: 1,
+            hash: dictionary_hash(&words),
+            size: 3,
+        };
+        assert!(res.contains(&Log::builder().dest(USER1).payload(sealed)));
+        assert!(!wordle.send(USER1, start_game(user, EN, 5)).main_failed());
+    }
+
+    #[test]
+    fn test_dictionary_is_owner_only() {
+        let sys = System::new();
+        sys.init_logger();
+        let wordle = setup(&sys, &["house"]);
+        let other_user = 11;
+
+        let add_words = |words: &[&str]| Action::AddWords {
+            language: EN.to_string(),
+            words: words.iter().map(|word| word.to_string()).collect(),
+        };
+        assert!(wordle.send(other_user, add_words(&["mouse"])).main_failed());
+        let seal = Action::SealDictionary { language: EN.to_string() };
+        assert!(wordle.send(other_user, seal).main_failed());
+        assert!(wordle.send(USER1, add_words(&["elephants"])).main_failed());
+        assert!(!wordle.send(USER1, add_words(&["mouse"])).main_failed());
+    }
+
+    #[test]
+    fn test_running_game_keeps_its_dictionary() {
+        let sys = System::new();
+        sys.init_logger();
+        let wordle = setup(&sys, &["house"]);
+        let user: ActorId = USER1.into();
+
+        assert!(!wordle.send(USER1, start_game(user, EN, 5)).main_failed());
+
+        // Replace the whole dictionary while the game is running
+        let (language, words) = (EN.to_string(), vec!["mouse".to_string()]);
+        wordle.send(USER1, Action::AddWords { language, words });
+        let (language, words) = (EN.to_string(), vec!["house".to_string()]);
+        wordle.send(USER1, Action::RemoveWords { language, words });
+        let res = wordle.send(USER1, Action::SealDictionary { language: EN.to_string() });
+        let sealed = Event::DictionarySealed {
+            language: EN.to_string(),
+            version: 2,
+            hash: dictionary_hash(&["mouse".to_string()]),
+            size: 1,
+        };
+        assert!(res.contains(&Log::builder().dest(USER1).payload(sealed)));
+
+        let res = wordle.send(
+            USER1,
+            Action::CheckWord {
+                user,
+                word: "house".to_string(),
+            },
+        );
+        let checked = Event::WordChecked {
+            user,
+            feedback: Feedback::new(&[LetterResult::Correct; 5]),
+        };
+        assert!(res.contains(&Log::builder().dest(USER1).payload(checked)));
+    }
+
+    #[test]
+    fn test_unknown_guess_rejected() {
+        let sys = System::new();
+        sys.init_logger();
+        let wordle = setup(&sys, &["house"]);
+        let user: ActorId = USER1.into();
+
+        assert!(!wordle.send(USER1, start_game(user, EN, 5)).main_failed());
+
+        for word in ["aeiou", "hous", "h3llo"] {
+            let word = word.to_string();
+            let res = wordle.send(USER1, Action::CheckWord { user, word: word.clone() });
+            assert!(res.contains(&Log::builder().dest(USER1).payload(Event::InvalidWord { user, word })));
+        }
+
+        // Allowed guesses are checked like any other word
+        use LetterResult::*;
+        let res = wordle.send(
+            USER1,
+            Action::CheckWord {
+                user,
+                word: "hello".to_string(),
+            },
+        );
+        let checked = Event::WordChecked {
+            user,
+            feedback: Feedback::new(&[Correct, Present, Absent, Absent, Present]),
+        };
+        assert!(res.contains(&Log::builder().dest(USER1).payload(checked)));
+
+        // The owner can extend the list of allowed guesses
+        let res = wordle.send(
+            USER1,
+            Action::AddAllowedGuesses {
+                language: EN.to_string(),
+                words: vec!["aeiou".to_string()],
+            },
+        );
+        assert!(res.contains(&Log::builder().dest(USER1).payload(Event::AllowedGuessesUpdated { size: 3 })));
+        let res = wordle.send(
+            USER1,
+            Action::CheckWord {
+                user,
+                word: "aeiou".to_string(),
+            },
+        );
+        assert!(!res.contains(&Log::builder().dest(USER1).payload(Event::InvalidWord {
+            user,
+            word: "aeiou".to_string(),
+        })));
+    }
+
+    #[test]
+    fn test_languages_are_separate() {
+        let sys = System::new();
+        sys.init_logger();
+        let wordle = setup(&sys, &["house"]);
+        let user: ActorId = USER1.into();
+
+        // Cyrillic and accented words are counted in letters, not bytes
+        let banks: [(&str, &[&str]); 2] = [("ru", &["книга", "мышка"]), ("de", &["Grüße"])];
+        for (language, words) in banks {
+            let action = Action::AddWords {
+                language: language.to_string(),
+                words: words.iter().map(|word| word.to_string()).collect(),
+            };
+            assert!(!wordle.send(USER1, action).main_failed());
+            let seal = Action::SealDictionary { language: language.to_string() };
+            assert!(!wordle.send(USER1, seal).main_failed());
+        }
+        assert!(wordle.send(USER1, start_game(user, "fr", 5)).main_failed());
+
+        assert!(!wordle.send(USER1, start_game(user, "ru", 5)).main_failed());
+        // English words are unknown in a Russian game
+        let word = "house".to_string();
+        let res = wordle.send(USER1, Action::CheckWord { user, word: word.clone() });
+        assert!(res.contains(&Log::builder().dest(USER1).payload(Event::InvalidWord { user, word })));
+
+        assert!(!wordle.send(USER1, Action::Abandon { user }).main_failed());
+        assert!(!wordle.send(USER1, start_game(user, "de", 5)).main_failed());
+        // The uppercase, decomposed spelling is the same word
+        let res = wordle.send(
+            USER1,
+            Action::CheckWord {
+                user,
+                word: "GRU\u{308}ßE".to_string(),
+            },
+        );
+        let checked = Event::WordChecked {
+            user,
+            feedback: Feedback::new(&[LetterResult::Correct; 5]),
+        };
+        assert!(res.contains(&Log::builder().dest(USER1).payload(checked)));
+    }
+
+    #[test]
+    fn test_word_lengths() {
+        let sys = System::new();
+        sys.init_logger();
+        let wordle = setup(&sys, &["tree", "house", "garden"]);
+        let user: ActorId = USER1.into();
+
+        // Outside of 4 to 8 letters, or no words of that length in the bank
+        for length in [3, 9, 7] {
+            assert!(wordle.send(USER1, start_game(user, EN, length)).main_failed());
+        }
+
+        assert!(!wordle.send(USER1, start_game(user, EN, 6)).main_failed());
+        // A known word of another length is not a valid guess
+        let word = "house".to_string();
+        let res = wordle.send(USER1, Action::CheckWord { user, word: word.clone() });
+        assert!(res.contains(&Log::builder().dest(USER1).payload(Event::InvalidWord { user, word })));
+
+        let res = wordle.send(
+            USER1,
+            Action::CheckWord {
+                user,
+                word: "garden".to_string(),
+            },
+        );
+        let checked = Event::WordChecked {
+            user,
+            feedback: Feedback::new(&[LetterResult::Correct; 6]),
+        };
+        assert!(res.contains(&Log::builder().dest(USER1).payload(checked)));
+    }
+
+    #[test]
+    fn test_hard_mode() {
+        let sys = System::new();
+        sys.init_logger();
+        let wordle = setup(&sys, &["house"]);
+        let user: ActorId = USER1.into();
+
+        let words = ["holes", "hymns"].iter().map(|word| word.to_string()).collect();
+        let language = EN.to_string();
+        assert!(!wordle.send(USER1, Action::AddAllowedGuesses { language, words }).main_failed());
+        let start_game = Action::StartGame {
+            user,
+            language: EN.to_string(),
+            length: 5,
+            hard_mode: true,
+            mode: GameMode::Classic,
+            boards: 1,
+            category: None,
+            force: false,
+        };
+        assert!(!wordle.send(USER1, start_game).main_failed());
+
+        // "h" is revealed in place, "o" and "e" elsewhere
+        let check = |word: &str| Action::CheckWord {
+            user,
+            word: word.to_string(),
+        };
+        assert!(!wordle.send(USER1, check("hello")).main_failed());
+
+        let violations = [
+            (
+                "world",
+                HardModeViolation::CorrectLetterMoved {
+                    position: 0,
+                    letter: "h".to_string(),
+                },
+            ),
+            (
+                "hymns",
+                HardModeViolation::MissingLetter {
+                    letter: "e".to_string(),
+                    count: 1,
+                },
+            ),
+        ];
+        for (word, violation) in violations {
+            let res = wordle.send(USER1, check(word));
+            let event = Event::HardModeViolation { user, violation };
+            assert!(res.contains(&Log::builder().dest(USER1).payload(event)));
+        }
+
+        use LetterResult::*;
+        let res = wordle.send(USER1, check("holes"));
+        let checked = Event::WordChecked {
+            user,
+            feedback: Feedback::new(&[Correct, Correct, Absent, Present, Present]),
+        };
+        assert!(res.contains(&Log::builder().dest(USER1).payload(checked)));
+    }
+
+    #[test]
+    fn test_daily_challenge() {
+        let sys = System::new();
+        sys.init_logger();
+        let wordle = Program::current(&sys);
+        let words = ["horse", "house", "mouse"];
+        let config = InitConfig {
+            daily: Some(DailyConfig { epoch_length: 100 }),
+            ..config(&words)
+        };
+        assert!(!wordle.send(USER1, config).main_failed());
+
+        // Every player acts for themselves
+        let start_daily = |user: u64| Action::StartGame {
+            user: user.into(),
+            language: EN.to_string(),
+            length: 5,
+            hard_mode: false,
+            mode: GameMode::Daily,
+            boards: 1,
+            category: None,
+            force: false,
+        };
+        let guess = |user: u64, word: &str| {
+            let res = wordle.send(
+                user,
+                Action::CheckWord {
+                    user: user.into(),
+                    word: word.to_string(),
+                },
+            );
+            events(&res)
+                .into_iter()
+                .find_map(|event| match event {
+                    Event::WordChecked { feedback, .. } => Some(feedback),
+                    _ => None,
+                })
+                .expect("The word is not checked")
+        };
+
+        // Both players get the same word
+        let (user1, user2) = (USER1, 11);
+        for user in [user1, user2] {
+            assert!(!wordle.send(user, start_daily(user)).main_failed());
+        }
+        let mut tries = 0;
+        let mut feedbacks = Vec::new();
+        for word in words {
+            tries += 1;
+            let feedback = guess(user1, word);
+            assert_eq!(guess(user2, word), feedback);
+            feedbacks.push(feedback);
+            if feedback.is_solved() {
+                break;
+            }
+        }
+
+        // Resealing in the middle of the epoch doesn't change the daily word
+        let to_strings = |words: &[&str]| words.iter().map(|word| word.to_string()).collect();
+        let language = EN.to_string();
+        wordle.send(USER1, Action::RemoveWords { language, words: to_strings(&words) });
+        let language = EN.to_string();
+        wordle.send(USER1, Action::AddWords { language, words: to_strings(&["louse", "lousy"]) });
+        assert!(!wordle.send(USER1, Action::SealDictionary { language: EN.to_string() }).main_failed());
+        let user3 = 12;
+        assert!(!wordle.send(user3, start_daily(user3)).main_failed());
+        for (word, feedback) in words.into_iter().zip(feedbacks) {
+            assert_eq!(guess(user3, word), feedback);
+        }
+
+        // Once per epoch
+        assert!(wordle.send(user1, start_daily(user1)).main_failed());
+
+        let res = wordle.send(USER1, Action::DailyLeaderboard { epoch: 0 });
+        let result = |user: u64| DailyResult {
+            user: user.into(),
+            language: EN.to_string(),
+            length: 5,
+            tries,
+            hints: 0,
+        };
+        let leaderboard = Event::DailyLeaderboard {
+            epoch: 0,
+            results: vec![result(user1), result(user2), result(user3)],
+        };
+        assert!(res.contains(&Log::builder().dest(USER1).payload(leaderboard)));
+
+        sys.spend_blocks(100);
+        assert!(!wordle.send(user1, start_daily(user1)).main_failed());
+    }
+
+    #[test]
+    fn test_trusted_callers() {
+        let sys = System::new();
+        sys.init_logger();
+        let wordle = setup(&sys, &["house"]);
+        let user: ActorId = USER1.into();
+        let (other_user, session) = (11, 12);
+
+        // Nobody else can start or play the game of the user
+        assert!(wordle.send(other_user, start_game(user, EN, 5)).main_failed());
+        assert!(!wordle.send(USER1, start_game(user, EN, 5)).main_failed());
+        let check = || Action::CheckWord {
+            user,
+            word: "hello".to_string(),
+        };
+        assert!(wordle.send(other_user, check()).main_failed());
+        assert!(wordle.send(session, check()).main_failed());
+
+        // Until the owner trusts the caller
+        let program: ActorId = session.into();
+        assert!(wordle.send(other_user, Action::AddTrustedCaller { program }).main_failed());
+        let res = wordle.send(USER1, Action::AddTrustedCaller { program });
+        assert!(res.contains(&Log::builder().dest(USER1).payload(Event::TrustedCallersUpdated { count: 1 })));
+        assert!(!wordle.send(session, check()).main_failed());
+        assert!(!wordle.send(session, start_game(user, EN, 5)).main_failed());
+
+        wordle.send(USER1, Action::RemoveTrustedCaller { program });
+        assert!(wordle.send(session, check()).main_failed());
+    }
+
+    #[test]
+    fn test_state_queries() {
+        let sys = System::new();
+        sys.init_logger();
+        let wordle = setup(&sys, &["house"]);
+        let user: ActorId = USER1.into();
+
+        let reply: StateReply = wordle.read_state(StateQuery::ActiveGames).unwrap();
+        assert!(matches!(reply, StateReply::ActiveGames(0)));
+        let query = StateQuery::Dictionary {
+            language: EN.to_string(),
+        };
+        let dictionary = DictionaryInfo {
+            version: 1,
+            size: 1,
+            hash: dictionary_hash(&["house".to_string()]),
+            categories: vec![],
+        };
+        let reply: StateReply = wordle.read_state(query).unwrap();
+        assert!(matches!(reply, StateReply::Dictionary(Some(info)) if info == dictionary));
+
+        let res = wordle.send(USER1, start_game(user, EN, 5));
+        let Some(Event::GameStarted { commitments, .. }) = events(&res).pop() else {
+            panic!("The game is not started");
+        };
+        wordle.send(
+            USER1,
+            Action::CheckWord {
+                user,
+                word: "hello".to_string(),
+            },
+        );
+        let game = GameInfo {
+            language: EN.to_string(),
+            length: 5,
+            category: None,
+            dictionary_version: 1,
+            mode: GameMode::Classic,
+            hard_mode: false,
+            daily_epoch: None,
+            tries: 1,
+            hints: 0,
+            boards: 1,
+            solved_boards: 0,
+            commitments,
+        };
+        let reply: StateReply = wordle.read_state(StateQuery::Game(user)).unwrap();
+        assert!(matches!(reply, StateReply::Game(Some(info)) if info == game));
+        let reply: StateReply = wordle.read_state(StateQuery::ActiveGames).unwrap();
+        assert!(matches!(reply, StateReply::ActiveGames(1)));
+
+        wordle.send(
+            USER1,
+            Action::CheckWord {
+                user,
+                word: "house".to_string(),
+            },
+        );
+        let reply: StateReply = wordle.read_state(StateQuery::Game(user)).unwrap();
+        assert!(matches!(reply, StateReply::Game(None)));
+        let stats = UserStats {
+            games_played: 1,
+            wins: 1,
+            losses: 0,
+            assisted_wins: 0,
+        };
+        let reply: StateReply = wordle.read_state(StateQuery::Stats(user)).unwrap();
+        assert!(matches!(reply, StateReply::Stats(info) if info == stats));
+    }
+
+    #[test]
+    fn test_large_dictionary() {
+        let sys = System::new();
+        sys.init_logger();
+        let wordle = setup(&sys, &[]);
+
+        // Far more words than a single random byte could pick from
+        let alphabet: Vec<char> = ('a'..='z').collect();
+        let mut words = Vec::new();
+        for a in &alphabet {
+            for b in &alphabet {
+                for c in &alphabet {
+                    words.push(format!("{a}{b}{c}ab"));
+                }
+            }
+        }
+        for chunk in words.chunks(1000) {
+            let action = Action::AddWords {
+                language: EN.to_string(),
+                words: chunk.to_vec(),
+            };
+            assert!(!wordle.send(USER1, action).main_failed());
+        }
+        let res = wordle.send(USER1, Action::SealDictionary { language: EN.to_string() });
+        let sealed = Event::DictionarySealed {
+            language: EN.to_string(),
+            version: 1,
+            hash: dictionary_hash(&words),
+            size: words.len() as u32,
+        };
+        assert!(res.contains(&Log::builder().dest(USER1).payload(sealed)));
+
+        for user in 100..110 {
+            assert!(!wordle.send(user, start_game(user.into(), EN, 5)).main_failed());
+        }
+        let reply: StateReply = wordle.read_state(StateQuery::ActiveGames).unwrap();
+        assert!(matches!(reply, StateReply::ActiveGames(10)));
+
+        // Absurdle scores every word of the bucket
+        let user: ActorId = USER1.into();
+        let absurdle = Action::StartGame {
+            user,
+            language: EN.to_string(),
+            length: 5,
+            hard_mode: false,
+            mode: GameMode::Absurdle,
+            boards: 1,
+            category: None,
+            force: false,
+        };
+        assert!(!wordle.send(USER1, absurdle).main_failed());
+        let check = Action::CheckWord {
+            user,
+            word: "abcab".to_string(),
+        };
+        assert!(!wordle.send(USER1, check).main_failed());
+    }
+
+    #[test]
+    fn test_no_repeated_answers() {
+        let sys = System::new();
+        sys.init_logger();
+        let words = ["horse", "house", "mouse"];
+        let wordle = setup(&sys, &words);
+        let user: ActorId = USER1.into();
+
+        // Guesses every word until the game reveals its answer
+        let play = || {
+            assert!(!wordle.send(USER1, start_game(user, EN, 5)).main_failed());
+            for word in words {
+                let action = Action::CheckWord {
+                    user,
+                    word: word.to_string(),
+                };
+                for event in events(&wordle.send(USER1, action)) {
+                    if let Event::GameRevealed { word, .. } = event {
+                        return word;
+                    }
+                }
+            }
+            panic!("The game is not over");
+        };
+
+        let mut answers: Vec<String> = (0..words.len()).map(|_| play()).collect();
+        answers.sort();
+        assert_eq!(answers, words);
+
+        // All words were seen, so they come round again
+        assert!(words.contains(&play().as_str()));
+    }
+
+    #[test]
+    fn test_game_lifecycle() {
+        let sys = System::new();
+        sys.init_logger();
+        let wordle = setup(&sys, &["house"]);
+        let user: ActorId = USER1.into();
+        let session = 12;
+
+        // An unfinished game isn't overwritten unless forced
+        assert!(!wordle.send(USER1, start_game(user, EN, 5)).main_failed());
+        let res = wordle.send(USER1, start_game(user, EN, 5));
+        assert!(res.contains(&Log::builder().dest(USER1).payload(Event::GameInProgress { user })));
+        let force_start = Action::StartGame {
+            user,
+            language: EN.to_string(),
+            length: 5,
+            hard_mode: false,
+            mode: GameMode::Classic,
+            boards: 1,
+            category: None,
+            force: true,
+        };
+        let res = wordle.send(USER1, force_start);
+        assert!(events(&res).iter().any(|event| matches!(event, Event::GameStarted { .. })));
+        assert!(events(&res).iter().any(|event| matches!(event, Event::GameRevealed { .. })));
+
+        let res = wordle.send(USER1, Action::Abandon { user });
+        let ended = Event::GameEnded {
+            user,
+            result: GameResult::Lost,
+        };
+        assert!(res.contains(&Log::builder().dest(USER1).payload(ended)));
+        let check = Action::CheckWord {
+            user,
+            word: "hello".to_string(),
+        };
+        assert!(wordle.send(USER1, check).main_failed());
+        assert!(wordle.send(USER1, Action::Abandon { user }).main_failed());
+
+        // Only trusted callers can report a result
+        assert!(!wordle.send(USER1, start_game(user, EN, 5)).main_failed());
+        let end_game = Action::EndGame {
+            user,
+            result: GameResult::Won,
+        };
+        assert!(wordle.send(USER1, end_game.clone()).main_failed());
+        let program: ActorId = session.into();
+        wordle.send(USER1, Action::AddTrustedCaller { program });
+        assert!(!wordle.send(session, end_game).main_failed());
+
+        let reply: StateReply = wordle.read_state(StateQuery::ActiveGames).unwrap();
+        assert!(matches!(reply, StateReply::ActiveGames(0)));
+        let stats = UserStats {
+            games_played: 3,
+            wins: 1,
+            losses: 2,
+            assisted_wins: 0,
+        };
+        let reply: StateReply = wordle.read_state(StateQuery::Stats(user)).unwrap();
+        assert!(matches!(reply, StateReply::Stats(info) if info == stats));
+    }
+
+    #[test]
+    fn test_absurdle() {
+        let sys = System::new();
+        sys.init_logger();
+        let wordle = setup(&sys, &["horse", "house", "louse", "mouse", "rouse"]);
+        let user: ActorId = USER1.into();
+
+        let absurdle = Action::StartGame {
+            user,
+            language: EN.to_string(),
+            length: 5,
+            hard_mode: false,
+            mode: GameMode::Absurdle,
+            boards: 1,
+            category: None,
+            force: false,
+        };
+        let res = wordle.send(USER1, absurdle);
+        let started = Event::GameStarted {
+            user,
+            commitments: vec![],
+        };
+        assert!(res.contains(&Log::builder().dest(USER1).payload(started)));
+
+        // Every guess dodges the answer while it can
+        use LetterResult::*;
+        for word in ["house", "mouse", "rouse"] {
+            let res = wordle.send(
+                USER1,
+                Action::CheckWord {
+                    user,
+                    word: word.to_string(),
+                },
+            );
+            let checked = Event::WordChecked {
+                user,
+                feedback: Feedback::new(&[Absent, Correct, Correct, Correct, Correct]),
+            };
+            assert!(res.contains(&Log::builder().dest(USER1).payload(checked)));
+        }
+        let reply: StateReply = wordle.read_state(StateQuery::Game(user)).unwrap();
+        assert!(matches!(reply, StateReply::Game(Some(info)) if info.mode == GameMode::Absurdle));
+
+        let res = wordle.send(
+            USER1,
+            Action::CheckWord {
+                user,
+                word: "louse".to_string(),
+            },
+        );
+        let revealed = Event::GameRevealed {
+            user,
+            board: 0,
+            word: "louse".to_string(),
+            salt: [0; 32],
+        };
+        assert!(res.contains(&Log::builder().dest(USER1).payload(revealed)));
+    }
+
+    #[test]
+    fn test_multiple_boards() {
+        let sys = System::new();
+        sys.init_logger();
+        let wordle = setup(&sys, &["horse", "house", "mouse"]);
+        let user: ActorId = USER1.into();
+
+        let start_boards = |boards: u8, hard_mode: bool| Action::StartGame {
+            user,
+            language: EN.to_string(),
+            length: 5,
+            hard_mode,
+            mode: GameMode::Classic,
+            boards,
+            category: None,
+            force: false,
+        };
+        assert!(wordle.send(USER1, start_boards(0, false)).main_failed());
+        assert!(wordle.send(USER1, start_boards(MAX_BOARDS + 1, false)).main_failed());
+        assert!(wordle.send(USER1, start_boards(2, true)).main_failed());
+        assert!(wordle.send(USER1, start_boards(4, false)).main_failed());
+
+        let res = wordle.send(USER1, start_boards(3, false));
+        let Some(Event::GameStarted { commitments, .. }) = events(&res).pop() else {
+            panic!("The game is not started");
+        };
+        assert_eq!(commitments.len(), 3);
+
+        // Every unsolved board is scored, solved boards drop out
+        let check = |word: &str| {
+            let action = Action::CheckWord {
+                user,
+                word: word.to_string(),
+            };
+            events(&wordle.send(USER1, action))
+        };
+        let boards = |events: &[Event]| match events.last() {
+            Some(Event::BoardsChecked { feedback, .. }) => {
+                feedback.iter().map(|board| board.board).collect::<Vec<_>>()
+            }
+            event => panic!("Unexpected event: {:?}", event),
+        };
+        let events = check("hello");
+        assert_eq!(boards(&events), [0, 1, 2]);
+        let events = check("horse");
+        assert_eq!(boards(&events), [0, 1, 2]);
+        let events = check("horse");
+        assert_eq!(boards(&events).len(), 2);
+        let reply: StateReply = wordle.read_state(StateQuery::Game(user)).unwrap();
+        assert!(matches!(
+            reply,
+            StateReply::Game(Some(info)) if info.boards == 3 && info.solved_boards == 1
+        ));
+
+        // All three words are revealed once the last board is solved
+        check("house");
+        let events = check("mouse");
+        let revealed: Vec<(u8, String, [u8; 32])> = events
+            .iter()
+            .filter_map(|event| match event {
+                Event::GameRevealed {
+                    board, word, salt, ..
+                } => Some((*board, word.clone(), *salt)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(revealed.len(), 3);
+        for (board, word, salt) in revealed {
+            assert!(verify_reveal(&commitments[board as usize], &word, &salt));
+        }
+        let reply: StateReply = wordle.read_state(StateQuery::Stats(user)).unwrap();
+        assert!(matches!(reply, StateReply::Stats(stats) if stats.wins == 1));
+
+        // Two boards allow seven tries
+        assert!(!wordle.send(USER1, start_boards(2, false)).main_failed());
+        for _ in 1..max_tries(2) {
+            check("hello");
+        }
+        let reply: StateReply = wordle.read_state(StateQuery::Game(user)).unwrap();
+        assert!(matches!(reply, StateReply::Game(Some(info)) if info.tries == 6));
+        let events = check("hello");
+        assert!(events.iter().any(|event| matches!(event, Event::GameRevealed { .. })));
+        let reply: StateReply = wordle.read_state(StateQuery::Game(user)).unwrap();
+        assert!(matches!(reply, StateReply::Game(None)));
+    }
+
+    #[test]
+    fn test_categories() {
+        let sys = System::new();
+        sys.init_logger();
+        let wordle = setup(&sys, &["house", "mouse"]);
+        let user: ActorId = USER1.into();
+
+        let language = || EN.to_string();
+        let animals = || "animals".to_string();
+        let words = |words: &[&str]| words.iter().map(|word| word.to_string()).collect();
+
+        // Categories are created by the owner before words are assigned
+        let add = Action::AddToCategory {
+            language: language(),
+            category: animals(),
+            words: words(&["Horse", "mouse"]),
+        };
+        assert!(wordle.send(USER1, add.clone()).main_failed());
+        let create = Action::CreateCategory {
+            language: language(),
+            category: "Week 3".to_string(),
+        };
+        assert!(wordle.send(USER1, create).main_failed());
+        let create = Action::CreateCategory {
+            language: language(),
+            category: animals(),
+        };
+        let other_user = 11;
+        assert!(wordle.send(other_user, create.clone()).main_failed());
+        assert!(!wordle.send(USER1, create).main_failed());
+
+        let res = wordle.send(USER1, add);
+        let updated = Event::CategoryUpdated {
+            language: language(),
+            category: animals(),
+            size: Some(2),
+        };
+        assert!(res.contains(&Log::builder().dest(USER1).payload(updated)));
+        let remove = Action::RemoveFromCategory {
+            language: language(),
+            category: animals(),
+            words: words(&["mouse"]),
+        };
+        assert!(!wordle.send(USER1, remove).main_failed());
+
+        // The category takes effect with the next sealed dictionary
+        let start_animals = || Action::StartGame {
+            user,
+            language: language(),
+            length: 5,
+            hard_mode: false,
+            mode: GameMode::Classic,
+            boards: 1,
+            category: Some(animals()),
+            force: false,
+        };
+        assert!(wordle.send(USER1, start_animals()).main_failed());
+        assert!(!wordle.send(USER1, Action::SealDictionary { language: language() }).main_failed());
+        let query = StateQuery::Dictionary {
+            language: language(),
+        };
+        let reply: StateReply = wordle.read_state(query).unwrap();
+        assert!(matches!(
+            reply,
+            StateReply::Dictionary(Some(info))
+                if info.size == 3 && info.categories == [(animals(), 1)]
+        ));
+
+        // Only "horse" is an animal, other words can still be guessed
+        for _ in 0..3 {
+            assert!(!wordle.send(USER1, start_animals()).main_failed());
+            let reply: StateReply = wordle.read_state(StateQuery::Game(user)).unwrap();
+            assert!(matches!(
+                reply,
+                StateReply::Game(Some(info)) if info.category == Some(animals())
+            ));
+            let check = Action::CheckWord {
+                user,
+                word: "house".to_string(),
+            };
+            assert!(!wordle.send(USER1, check).main_failed());
+            let check = Action::CheckWord {
+                user,
+                word: "horse".to_string(),
+            };
+            let revealed = events(&wordle.send(USER1, check)).into_iter().any(|event| {
+                matches!(event, Event::GameRevealed { word, .. } if word == "horse")
+            });
+            assert!(revealed);
+        }
+
+        // Words removed from the draft leave their categories
+        let remove = Action::RemoveWords {
+            language: language(),
+            words: words(&["horse"]),
+        };
+        assert!(!wordle.send(USER1, remove).main_failed());
+        assert!(!wordle.send(USER1, Action::SealDictionary { language: language() }).main_failed());
+        assert!(wordle.send(USER1, start_animals()).main_failed());
+
+        let remove = Action::RemoveCategory {
+            language: language(),
+            category: animals(),
+        };
+        assert!(!wordle.send(USER1, remove.clone()).main_failed());
+        assert!(wordle.send(USER1, remove).main_failed());
+    }
+
+    #[test]
+    fn test_hints() {
+        let sys = System::new();
+        sys.init_logger();
+        let words = ["horse", "house", "louse", "mouse", "rouse"];
+        let wordle = setup(&sys, &words);
+        let user: ActorId = USER1.into();
+
+        assert!(wordle.send(USER1, Action::Hint { user }).main_failed());
+        assert!(!wordle.send(USER1, start_game(user, EN, 5)).main_failed());
+        let hint = || match events(&wordle.send(USER1, Action::Hint { user }))[..] {
+            [Event::Hint {
+                board: 0,
+                ref word,
+                hints_left,
+                ..
+            }] => (word.clone(), hints_left),
+            ref events => panic!("Unexpected events: {:?}", events),
+        };
+
+        // Every hint is consistent with the feedback so far and doesn't count as a try
+        let (word, hints_left) = hint();
+        assert!(words.contains(&word.as_str()));
+        assert_eq!(hints_left, MAX_HINTS - 1);
+        let check = |word: &str| {
+            let action = Action::CheckWord {
+                user,
+                word: word.to_string(),
+            };
+            events(&wordle.send(USER1, action))
+        };
+        let mut answer = None;
+        for event in check(&word) {
+            if let Event::GameRevealed { word, .. } = event {
+                answer = Some(word);
+            }
+        }
+        while answer.is_none() {
+            let (word, _) = hint();
+            for event in check(&word) {
+                if let Event::GameRevealed { word, .. } = event {
+                    answer = Some(word);
+                }
+            }
+        }
+        let reply: StateReply = wordle.read_state(StateQuery::Stats(user)).unwrap();
+        assert!(matches!(
+            reply,
+            StateReply::Stats(stats) if stats.wins == 1 && stats.assisted_wins == 1
+        ));
+
+        // A win without hints is not marked as assisted
+        assert!(!wordle.send(USER1, start_game(user, EN, 5)).main_failed());
+        let won = words.iter().any(|word| {
+            check(word)
+                .into_iter()
+                .any(|event| matches!(event, Event::GameRevealed { .. }))
+        });
+        assert!(won);
+        let reply: StateReply = wordle.read_state(StateQuery::Stats(user)).unwrap();
+        assert!(matches!(
+            reply,
+            StateReply::Stats(stats) if stats.wins == 2 && stats.assisted_wins == 1
+        ));
+
+        // The budget runs out
+        assert!(!wordle.send(USER1, start_game(user, EN, 5)).main_failed());
+        for _ in 0..MAX_HINTS {
+            hint();
+        }
+        assert!(wordle.send(USER1, Action::Hint { user }).main_failed());
+        let reply: StateReply = wordle.read_state(StateQuery::Game(user)).unwrap();
+        assert!(matches!(
+            reply,
+            StateReply::Game(Some(info)) if info.hints == MAX_HINTS && info.tries == 0
+        ));
+    }
+}
@@ -0,0 +1,213 @@
+#[cfg(test)]
+mod tests {
+    use gstd::{prelude::*, ActorId};
+    use gtest::{Program, System};
+    use wordle_io::*;
+
+    const USER1: u64 = 10;
+    const EN: &str = "en";
+    // Built into the shared workspace target by the baseline build script, which runs first
+    // since wordle dev-depends on the baseline
+    const BASELINE_WASM: &str =
+        "../target/wasm32-unknown-unknown/debug/wordle_string_baseline.opt.wasm";
+
+    fn config(words: &[String]) -> InitConfig {
+        InitConfig {
+            language: EN.to_string(),
+            words: words.to_vec(),
+            owner: USER1.into(),
+            allowed_guesses: vec![],
+            daily: None,
+            trusted_callers: vec![],
+        }
+    }
+
+    fn add_words(wordle: &Program, words: &[String]) {
+        for chunk in words.chunks(1000) {
+            let action = Action::AddWords {
+                language: EN.to_string(),
+                words: chunk.to_vec(),
+            };
+            assert!(!wordle.send(USER1, action).main_failed());
+        }
+    }
+
+    fn seal(wordle: &Program) {
+        let seal = Action::SealDictionary {
+            language: EN.to_string(),
+        };
+        assert!(!wordle.send(USER1, seal).main_failed());
+    }
+
+    fn setup<'a>(sys: &'a System, words: &[String]) -> Program<'a> {
+        let wordle = Program::current(sys);
+        assert!(!wordle.send(USER1, config(&[])).main_failed());
+        add_words(&wordle, words);
+        seal(&wordle);
+        wordle
+    }
+
+    // The same actions played by a program that keeps the words of the bank in sorted String
+    // buckets and a String per game
+    fn setup_baseline<'a>(sys: &'a System, words: &[String]) -> Program<'a> {
+        let baseline = Program::from_file(sys, BASELINE_WASM);
+        assert!(!baseline.send(USER1, config(words)).main_failed());
+        baseline
+    }
+
+    // Program memory in bytes, it grows a 64 KiB page at a time
+    fn memory(program: &Program) -> usize {
+        let reply: StateReply = program.read_state(StateQuery::MemoryPages).unwrap();
+        let StateReply::MemoryPages(pages) = reply else {
+            panic!("Unexpected state reply: {:?}", reply);
+        };
+        pages as usize * 64 * 1024
+    }
+
+    fn start_game(user: ActorId, mode: GameMode) -> Action {
+        Action::StartGame {
+            user,
+            language: EN.to_string(),
+            length: 5,
+            hard_mode: false,
+            mode,
+            boards: 1,
+            category: None,
+            force: false,
+        }
+    }
+
+    // 26^3 words "aaaab" to "zzzab"
+    fn words() -> Vec<String> {
+        let alphabet: Vec<char> = ('a'..='z').collect();
+        let mut words = Vec::new();
+        for a in &alphabet {
+            for b in &alphabet {
+                for c in &alphabet {
+                    words.push(format!("{a}{b}{c}ab"));
+                }
+            }
+        }
+        words
+    }
+
+    // Gas burned by a first guess in a classic and in an Absurdle game, and in a game of the
+    // String baseline
+    fn check_word_gas(words: &[String]) -> (u64, u64, u64) {
+        let sys = System::new();
+        sys.init_logger();
+        let wordle = setup(&sys, words);
+        let baseline = setup_baseline(&sys, words);
+        let user: ActorId = USER1.into();
+        let check = || Action::CheckWord {
+            user,
+            word: "zzzab".to_string(),
+        };
+
+        assert!(!wordle.send(USER1, start_game(user, GameMode::Classic)).main_failed());
+        let res = wordle.send(USER1, check());
+        assert!(!res.main_failed());
+        let classic = res.main_gas_burned().0;
+        assert!(!wordle.send(USER1, Action::Abandon { user }).main_failed());
+
+        assert!(!wordle.send(USER1, start_game(user, GameMode::Absurdle)).main_failed());
+        let res = wordle.send(USER1, check());
+        assert!(!res.main_failed());
+        let absurdle = res.main_gas_burned().0;
+
+        assert!(!baseline.send(USER1, start_game(user, GameMode::Classic)).main_failed());
+        let res = baseline.send(USER1, check());
+        assert!(!res.main_failed());
+        (classic, absurdle, res.main_gas_burned().0)
+    }
+
+    #[test]
+    #[ignore = "sends thousands of messages, run with --ignored"]
+    fn bench_check_word_gas() {
+        let words = words();
+        let small = check_word_gas(&words[words.len() - 1000..]);
+        let full = check_word_gas(&words);
+        println!(
+            "CheckWord gas (classic, absurdle, string baseline): {:?} with 1000 words, {:?} with {} words",
+            small,
+            full,
+            words.len()
+        );
+
+        // A classic guess is scored against the packed answer only, so it costs about the
+        // same whatever the size of the bucket
+        assert!(full.0 * 2 < small.0 * 3);
+        // Absurdle scores every word of the bucket
+        assert!(small.0 < small.1);
+        assert!(full.0 < full.1);
+        assert!(small.1 < full.1);
+    }
+
+    #[test]
+    fn bench_dictionary_memory() {
+        let words = words();
+
+        // Sealing packs the words of the draft into the dictionary games are played with
+        let sys = System::new();
+        let wordle = Program::current(&sys);
+        assert!(!wordle.send(USER1, config(&[])).main_failed());
+        add_words(&wordle, &words);
+        let draft = memory(&wordle);
+        seal(&wordle);
+        let packed = memory(&wordle) - draft;
+
+        // The baseline keeps a String per word
+        let empty = setup_baseline(&sys, &[]);
+        let full = setup_baseline(&sys, &words);
+        let strings = memory(&full) - memory(&empty);
+        println!(
+            "Program memory for a dictionary of {} words: {} bytes packed, {} bytes as strings",
+            words.len(),
+            packed,
+            strings
+        );
+        assert!(packed * 2 < strings);
+    }
+
+    #[test]
+    #[ignore = "sends more than 20k messages, run with --ignored"]
+    fn bench_concurrent_games() {
+        let words = words();
+        let users = 1_000..11_000;
+        // Program memory 10000 games take on top of the bank
+        let games_memory = |program: &Program| {
+            let before = memory(program);
+            for user in users.clone() {
+                assert!(!program.send(user, start_game(user.into(), GameMode::Classic)).main_failed());
+            }
+            let reply: StateReply = program.read_state(StateQuery::ActiveGames).unwrap();
+            assert!(matches!(reply, StateReply::ActiveGames(10_000)));
+            memory(program) - before
+        };
+
+        let sys = System::new();
+        let wordle = setup(&sys, &words[..1000]);
+        let baseline = setup_baseline(&sys, &words[..1000]);
+        let packed = games_memory(&wordle);
+        let strings = games_memory(&baseline);
+
+        // The last games are still playable
+        for user in users.clone().rev().take(10) {
+            let check = Action::CheckWord {
+                user: user.into(),
+                word: words[0].clone(),
+            };
+            assert!(!wordle.send(user, check).main_failed());
+        }
+
+        // A game keeps an index into the shared packed bucket instead of its own word, but also
+        // its boards, guesses and the player's stats and seen answers the baseline doesn't have
+        println!(
+            "Program memory for {} games: {} bytes with packed words, {} bytes with a String each",
+            users.len(),
+            packed,
+            strings
+        );
+        assert!(packed < users.len() * 2048);
+    }
+}
@@ -1,33 +1,947 @@
 #![no_std]
 
-use gmeta::{InOut, Metadata};
-use gstd::{string::String, vec::Vec, ActorId, Decode, Encode, TypeInfo};
+use blake2::{digest::consts::U32, Blake2b, Digest};
+use gmeta::{In, InOut, Metadata};
+use gstd::{
+    cmp::Ordering,
+    collections::{BTreeMap, BTreeSet},
+    ops::RangeInclusive,
+    string::String,
+    vec::Vec,
+    ActorId, Decode, Encode, TypeInfo,
+};
+use unicode_normalization::UnicodeNormalization;
 
 pub struct WordleMetadata;
 
 impl Metadata for WordleMetadata {
-    type Init = ();
+    type Init = In<InitConfig>;
     type Handle = InOut<Action, Event>;
     type Others = ();
     type Reply = ();
     type Signal = ();
-    type State = ();
+    type State = InOut<StateQuery, StateReply>;
+}
+
+#[derive(Debug, Clone, Encode, Decode, TypeInfo)]
+pub struct InitConfig {
+    /// Language of the initial word bank, e.g. `en`, `de` or `ru`.
+    pub language: String,
+    /// Words of any supported length, games pick from the ones of the requested length.
+    pub words: Vec<String>,
+    pub owner: ActorId,
+    /// Extra words accepted as guesses on top of the possible answers.
+    pub allowed_guesses: Vec<String>,
+    /// Enables the daily challenge.
+    pub daily: Option<DailyConfig>,
+    /// Programs, such as `game-session`, allowed to play on behalf of any user.
+    pub trusted_callers: Vec<ActorId>,
+}
+
+#[derive(Debug, Clone, Encode, Decode, TypeInfo)]
+pub struct DailyConfig {
+    /// Blocks in a day, the daily word changes every epoch. It's drawn from a seed generated
+    /// when the epoch is first played, so it can't be computed in advance.
+    pub epoch_length: u32,
+}
+
+/// A player who solved the daily word.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, TypeInfo)]
+pub struct DailyResult {
+    pub user: ActorId,
+    pub language: String,
+    pub length: u8,
+    pub tries: u32,
+    /// Hints the player took, an assisted result if not zero.
+    pub hints: u32,
+}
+
+impl InitConfig {
+    /// The word bank may be left empty and uploaded later with `Action::AddWords`.
+    pub fn assert_valid(&self) {
+        assert!(!self.owner.is_zero(), "Invalid owner");
+        assert_valid_language(&self.language);
+        let mut seen = BTreeSet::new();
+        for word in &self.words {
+            let word = normalize_word(word);
+            assert_valid_word(&word);
+            assert!(seen.insert(word), "Duplicate word in the bank");
+        }
+        for word in &self.allowed_guesses {
+            assert_valid_word(&normalize_word(word));
+        }
+        if let Some(daily) = &self.daily {
+            assert!(daily.epoch_length > 0, "The epoch can't be empty");
+        }
+    }
+}
+
+/// Longest word whose feedback fits in [`Feedback`].
+pub const MAX_WORD_LENGTH: usize = 16;
+/// Accepted guesses after which an unsolved single-board game is over.
+pub const MAX_TRIES: u32 = 6;
+/// Most boards a game can have, as in Octordle.
+pub const MAX_BOARDS: u8 = 8;
+/// Hints a player can ask for in one game.
+pub const MAX_HINTS: u32 = 3;
+/// Most candidates [`suggest_guess`] tries as a guess, which bounds its cost on large sets.
+pub const HINT_GUESSES: usize = 64;
+/// Most candidates each guess is scored against in [`suggest_guess`].
+pub const HINT_ANSWERS: usize = 512;
+/// Longest category name in bytes.
+pub const MAX_CATEGORY_LENGTH: usize = 32;
+/// Word lengths a game can be played with.
+pub const WORD_LENGTHS: RangeInclusive<u8> = 4..=8;
+
+/// Accepted guesses a game with this many boards can take, one more for every extra board.
+pub fn max_tries(boards: u8) -> u32 {
+    MAX_TRIES - 1 + boards as u32
+}
+
+pub fn assert_valid_language(language: &str) {
+    assert!(
+        !language.is_empty() && language.bytes().all(|b| b.is_ascii_lowercase()),
+        "Invalid language tag"
+    );
+}
+
+/// Category names, e.g. `animals` or `week-3`, are short lowercase ASCII with digits and dashes.
+pub fn assert_valid_category(category: &str) {
+    assert!(
+        (1..=MAX_CATEGORY_LENGTH).contains(&category.len())
+            && category
+                .bytes()
+                .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-'),
+        "Invalid category name"
+    );
+}
+
+/// Lowercases the word and brings it to the composed Unicode form, so that the same word
+/// typed in different ways is stored and compared as one.
+pub fn normalize_word(word: &str) -> String {
+    word.chars().flat_map(char::to_lowercase).nfc().collect()
+}
+
+/// Length of the word in letters rather than bytes.
+pub fn word_length(word: &str) -> u8 {
+    word.chars().count().try_into().unwrap_or(u8::MAX)
+}
+
+/// Checks a normalized word.
+pub fn assert_valid_word(word: &str) {
+    assert!(
+        WORD_LENGTHS.contains(&word_length(word)),
+        "Words must be 4 to 8 letters long"
+    );
+    assert!(
+        word.chars().all(|c| c.is_alphabetic() && !c.is_uppercase()),
+        "Words must be made of lowercase letters"
+    );
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode, TypeInfo)]
+pub enum LetterResult {
+    Absent,
+    Present,
+    Correct,
+}
+
+/// Feedback for a whole guess packed two bits per letter, the first letter in the lowest bits.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Encode, Decode, TypeInfo)]
+pub struct Feedback {
+    pub length: u8,
+    pub packed: u32,
+}
+
+impl Feedback {
+    pub fn new(results: &[LetterResult]) -> Self {
+        assert!(results.len() <= MAX_WORD_LENGTH, "Too many letters for the feedback");
+        let packed = results
+            .iter()
+            .enumerate()
+            .fold(0, |packed, (i, result)| packed | (*result as u32) << (2 * i));
+        Self {
+            length: results.len() as u8,
+            packed,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.length as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    pub fn get(&self, index: usize) -> LetterResult {
+        match (self.packed >> (2 * index)) & 0b11 {
+            2 => LetterResult::Correct,
+            1 => LetterResult::Present,
+            _ => LetterResult::Absent,
+        }
+    }
+
+    pub fn results(&self) -> Vec<LetterResult> {
+        (0..self.len()).map(|i| self.get(i)).collect()
+    }
+
+    pub fn is_solved(&self) -> bool {
+        !self.is_empty() && (0..self.len()).all(|i| self.get(i) == LetterResult::Correct)
+    }
+}
+
+/// A word as a fixed array of letters, so that scoring it needs no allocation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Letters {
+    letters: [char; MAX_WORD_LENGTH],
+    length: u8,
+}
+
+impl Letters {
+    /// Letters past [`MAX_WORD_LENGTH`] are dropped.
+    pub fn new(word: &str) -> Self {
+        let mut letters = ['\0'; MAX_WORD_LENGTH];
+        let mut length = 0;
+        for (slot, letter) in letters.iter_mut().zip(word.chars()) {
+            *slot = letter;
+            length += 1;
+        }
+        Self { letters, length }
+    }
+
+    pub fn len(&self) -> usize {
+        self.length as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    pub fn as_slice(&self) -> &[char] {
+        &self.letters[..self.len()]
+    }
+}
+
+/// Scores a guess the way Wordle does: exact matches are taken first, then letters present
+/// elsewhere, so each answer letter is counted at most as many times as it occurs.
+pub fn check_word(answer: &str, guess: &str) -> Feedback {
+    check_letters(&Letters::new(answer), &Letters::new(guess))
+}
+
+/// [`check_word`] on fixed arrays, everything stays on the stack since Absurdle and hints
+/// score whole buckets.
+pub fn check_letters(answer: &Letters, guess: &Letters) -> Feedback {
+    let mut results = [LetterResult::Absent; MAX_WORD_LENGTH];
+    let length = answer.len().min(guess.len());
+    let (answer, guess) = (&answer.as_slice()[..length], &guess.as_slice()[..length]);
+
+    // Answer letters left over after the exact matches
+    let mut unmatched = ['\0'; MAX_WORD_LENGTH];
+    let mut unmatched_len = 0;
+    for (i, (a, b)) in answer.iter().zip(guess).enumerate() {
+        if a == b {
+            results[i] = LetterResult::Correct;
+        } else {
+            unmatched[unmatched_len] = *a;
+            unmatched_len += 1;
+        }
+    }
+
+    for (i, b) in guess.iter().enumerate() {
+        if results[i] == LetterResult::Correct {
+            continue;
+        }
+        if let Some(pos) = unmatched[..unmatched_len].iter().position(|c| c == b) {
+            unmatched_len -= 1;
+            unmatched.swap(pos, unmatched_len);
+            results[i] = LetterResult::Present;
+        }
+    }
+    Feedback::new(&results[..length])
+}
+
+/// Indexed words the solver functions work on, plain strings or [`PackedWords`].
+pub trait WordList {
+    fn word_count(&self) -> usize;
+    fn letters(&self, index: usize) -> Letters;
+}
+
+impl<S: AsRef<str>> WordList for [S] {
+    fn word_count(&self) -> usize {
+        self.len()
+    }
+
+    fn letters(&self, index: usize) -> Letters {
+        Letters::new(self[index].as_ref())
+    }
+}
+
+impl<S: AsRef<str>> WordList for Vec<S> {
+    fn word_count(&self) -> usize {
+        self.len()
+    }
+
+    fn letters(&self, index: usize) -> Letters {
+        Letters::new(self[index].as_ref())
+    }
+}
+
+/// Absurdle scoring: groups the candidates by the feedback the guess would get and keeps the
+/// largest group, on a tie the one with the fewest hints, then the smallest packed feedback.
+/// Returns the feedback of that group.
+pub fn narrow_candidates<W: WordList + ?Sized>(
+    words: &W,
+    candidates: &mut IndexSet,
+    guess: &str,
+) -> Feedback {
+    let guess = Letters::new(guess);
+    let scored: Vec<(usize, Feedback)> = candidates
+        .iter()
+        .map(|index| (index, check_letters(&words.letters(index), &guess)))
+        .collect();
+    let mut groups: BTreeMap<u32, u32> = BTreeMap::new();
+    for (_, feedback) in &scored {
+        *groups.entry(feedback.packed).or_default() += 1;
+    }
+    let (&packed, _) = groups
+        .iter()
+        .max_by(|(packed_a, size_a), (packed_b, size_b)| {
+            size_a
+                .cmp(size_b)
+                .then(hint_count(**packed_b).cmp(&hint_count(**packed_a)))
+                .then(packed_b.cmp(packed_a))
+        })
+        .expect("No candidates left");
+
+    candidates.clear();
+    let mut kept = Feedback::default();
+    for (index, feedback) in scored {
+        if feedback.packed == packed {
+            candidates.insert(index);
+            kept = feedback;
+        }
+    }
+    kept
+}
+
+/// Number of letters that are not [`LetterResult::Absent`] in a packed feedback.
+fn hint_count(packed: u32) -> u32 {
+    ((packed | packed >> 1) & 0x5555_5555).count_ones()
+}
+
+/// Drops the candidates that would not have given the guess this feedback. Applied to every
+/// guess of a game it leaves the words consistent with all feedback so far, on chain for hints
+/// or in an off-chain solver.
+pub fn filter_candidates<W: WordList + ?Sized>(
+    words: &W,
+    candidates: &mut IndexSet,
+    guess: &str,
+    feedback: &Feedback,
+) {
+    let guess = Letters::new(guess);
+    let rejected: Vec<usize> = candidates
+        .iter()
+        .filter(|index| check_letters(&words.letters(*index), &guess) != *feedback)
+        .collect();
+    for index in rejected {
+        candidates.remove(index);
+    }
+}
+
+/// Picks the candidate that is expected to leave the fewest candidates after it is guessed:
+/// the one whose feedback splits the candidates into groups with the smallest sum of squared
+/// sizes. At most [`HINT_GUESSES`] candidates spread over the set are tried, each against at
+/// most [`HINT_ANSWERS`] of them, so a hint costs about the same in a bank of any size. On a tie
+/// the smallest index wins. `None` if there are no candidates.
+pub fn suggest_guess<W: WordList + ?Sized>(words: &W, candidates: &IndexSet) -> Option<usize> {
+    let len = candidates.len();
+    let sample = |limit: usize| -> Vec<(usize, Letters)> {
+        candidates
+            .iter()
+            .step_by(len.div_ceil(limit).max(1))
+            .map(|index| (index, words.letters(index)))
+            .collect()
+    };
+    let answers = sample(HINT_ANSWERS);
+    let mut best: Option<(u64, usize)> = None;
+    let mut groups = Vec::with_capacity(answers.len());
+    for (index, guess) in sample(HINT_GUESSES) {
+        groups.clear();
+        groups.extend(answers.iter().map(|(_, other)| check_letters(other, &guess).packed));
+        groups.sort_unstable();
+        let mut score = 0;
+        let mut start = 0;
+        for end in 1..=groups.len() {
+            if end == groups.len() || groups[end] != groups[start] {
+                score += ((end - start) as u64).pow(2);
+                start = end;
+            }
+        }
+        if !best.is_some_and(|(best_score, _)| best_score <= score) {
+            best = Some((score, index));
+        }
+    }
+    best.map(|(_, index)| index)
+}
+
+/// Hints revealed so far in a hard mode game, every later guess has to respect them.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct HardModeConstraints {
+    /// Letters known to be in place, by position.
+    pub correct: Vec<Option<char>>,
+    /// Least number of times each revealed letter has to be used.
+    pub required: BTreeMap<char, u8>,
+}
+
+/// The hint a hard mode guess ignored.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, TypeInfo)]
+pub enum HardModeViolation {
+    /// A letter revealed as correct isn't kept in its place.
+    CorrectLetterMoved { position: u8, letter: String },
+    /// A revealed letter isn't used as many times as it was revealed.
+    MissingLetter { letter: String, count: u8 },
+}
+
+impl HardModeConstraints {
+    pub fn check(&self, guess: &str) -> Result<(), HardModeViolation> {
+        let letters: Vec<char> = guess.chars().collect();
+        for (position, letter) in self.correct.iter().enumerate() {
+            if let Some(letter) = letter {
+                if letters.get(position) != Some(letter) {
+                    return Err(HardModeViolation::CorrectLetterMoved {
+                        position: position as u8,
+                        letter: (*letter).into(),
+                    });
+                }
+            }
+        }
+        for (letter, &count) in &self.required {
+            if letters.iter().filter(|c| *c == letter).count() < count as usize {
+                return Err(HardModeViolation::MissingLetter {
+                    letter: (*letter).into(),
+                    count,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds the hints revealed by the feedback for an accepted guess.
+    pub fn update(&mut self, guess: &str, feedback: &Feedback) {
+        let mut revealed: BTreeMap<char, u8> = BTreeMap::new();
+        for (position, letter) in guess.chars().enumerate() {
+            match feedback.get(position) {
+                LetterResult::Correct => {
+                    if self.correct.len() <= position {
+                        self.correct.resize(position + 1, None);
+                    }
+                    self.correct[position] = Some(letter);
+                }
+                LetterResult::Present => {}
+                LetterResult::Absent => continue,
+            }
+            *revealed.entry(letter).or_default() += 1;
+        }
+        for (letter, count) in revealed {
+            let required = self.required.entry(letter).or_default();
+            *required = (*required).max(count);
+        }
+    }
+}
+
+/// Compact set of dictionary indexes, one bit per word.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Encode, Decode, TypeInfo)]
+pub struct IndexSet {
+    blocks: Vec<u64>,
+}
+
+impl IndexSet {
+    /// Every index below `len`.
+    pub fn with_all(len: usize) -> Self {
+        let mut blocks = Vec::new();
+        blocks.resize(len / 64, u64::MAX);
+        if len % 64 != 0 {
+            blocks.push((1 << (len % 64)) - 1);
+        }
+        Self { blocks }
+    }
+
+    pub fn len(&self) -> usize {
+        self.blocks.iter().map(|block| block.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.blocks.iter().all(|block| *block == 0)
+    }
+
+    pub fn contains(&self, index: usize) -> bool {
+        self.blocks
+            .get(index / 64)
+            .is_some_and(|block| block & (1 << (index % 64)) != 0)
+    }
+
+    /// Returns `false` if the index was already in the set.
+    pub fn insert(&mut self, index: usize) -> bool {
+        if self.blocks.len() <= index / 64 {
+            self.blocks.resize(index / 64 + 1, 0);
+        }
+        let block = &mut self.blocks[index / 64];
+        let bit = 1 << (index % 64);
+        let inserted = *block & bit == 0;
+        *block |= bit;
+        inserted
+    }
+
+    /// Returns `false` if the index was not in the set.
+    pub fn remove(&mut self, index: usize) -> bool {
+        let Some(block) = self.blocks.get_mut(index / 64) else {
+            return false;
+        };
+        let bit = 1 << (index % 64);
+        let removed = *block & bit != 0;
+        *block &= !bit;
+        removed
+    }
+
+    pub fn clear(&mut self) {
+        self.blocks.clear();
+    }
+
+    /// Indexes in the set, smallest first.
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.blocks.iter().enumerate().flat_map(|(i, &block)| {
+            let mut block = block;
+            core::iter::from_fn(move || {
+                (block != 0).then(|| {
+                    let bit = block.trailing_zeros() as usize;
+                    block &= block - 1;
+                    i * 64 + bit
+                })
+            })
+        })
+    }
+
+    /// The `n`-th smallest index that is not in the set, counting from zero.
+    pub fn nth_absent(&self, mut n: usize) -> usize {
+        for (i, block) in self.blocks.iter().enumerate() {
+            let absent = block.count_zeros() as usize;
+            if n < absent {
+                // Drop the lowest `n` absent bits, the next one is the answer
+                let mut block = !block;
+                for _ in 0..n {
+                    block &= block - 1;
+                }
+                return i * 64 + block.trailing_zeros() as usize;
+            }
+            n -= absent;
+        }
+        self.blocks.len() * 64 + n
+    }
+}
+
+/// Bytes a packed letter takes at most: its code point in big-endian order, so byte order
+/// matches letter order.
+const MAX_LETTER_SIZE: usize = 3;
+
+// Bytes the code point of a letter needs, Latin-1 letters fit in one
+fn letter_size(letter: char) -> u8 {
+    match letter as u32 {
+        0..=0xff => 1,
+        0x100..=0xffff => 2,
+        _ => 3,
+    }
+}
+
+/// Sorted words of one length packed back to back, looked up with a binary search. Every
+/// letter takes as many bytes as the widest letter of the list needs.
+#[derive(Debug, Default, Clone, Encode, Decode, TypeInfo)]
+pub struct PackedWords {
+    word_length: u8,
+    letter_size: u8,
+    bytes: Vec<u8>,
+}
+
+impl PackedWords {
+    pub fn new(word_length: u8) -> Self {
+        assert!(word_length as usize <= MAX_WORD_LENGTH, "Words are too long to pack");
+        Self {
+            word_length,
+            letter_size: 1,
+            bytes: Vec::new(),
+        }
+    }
+
+    fn word_size(&self) -> usize {
+        self.word_length as usize * self.letter_size as usize
+    }
+
+    pub fn len(&self) -> usize {
+        match self.word_size() {
+            0 => 0,
+            size => self.bytes.len() / size,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    /// Bytes the packed words take.
+    pub fn packed_size(&self) -> usize {
+        self.bytes.len()
+    }
+
+    pub fn get(&self, index: usize) -> String {
+        self.letters(index).as_slice().iter().collect()
+    }
+
+    fn packed(&self, index: usize) -> &[u8] {
+        let size = self.word_size();
+        &self.bytes[index * size..(index + 1) * size]
+    }
+
+    // Packs the word into `buffer`, `None` if it has a different number of letters or a
+    // letter wider than the list stores
+    fn pack<'a>(
+        &self,
+        word: &str,
+        buffer: &'a mut [u8; MAX_LETTER_SIZE * MAX_WORD_LENGTH],
+    ) -> Option<&'a [u8]> {
+        let size = self.letter_size as usize;
+        let mut length = 0;
+        for (i, letter) in word.chars().enumerate() {
+            if i == self.word_length as usize || letter_size(letter) > self.letter_size {
+                return None;
+            }
+            let code = (letter as u32).to_be_bytes();
+            buffer[i * size..(i + 1) * size].copy_from_slice(&code[4 - size..]);
+            length = i + 1;
+        }
+        (length == self.word_length as usize).then_some(&buffer[..self.word_size()])
+    }
+
+    // Repacks every word with wider letters, zero bytes in front keep the order
+    fn widen(&mut self, letter_size: u8) {
+        let (old, new) = (self.letter_size as usize, letter_size as usize);
+        let mut bytes = Vec::with_capacity(self.bytes.len() / old.max(1) * new);
+        for letter in self.bytes.chunks(old.max(1)) {
+            bytes.resize(bytes.len() + new - old, 0);
+            bytes.extend_from_slice(letter);
+        }
+        self.letter_size = letter_size;
+        self.bytes = bytes;
+    }
+
+    fn search(&self, word: &[u8]) -> Result<usize, usize> {
+        let (mut low, mut high) = (0, self.len());
+        while low < high {
+            let mid = low + (high - low) / 2;
+            match self.packed(mid).cmp(word) {
+                Ordering::Less => low = mid + 1,
+                Ordering::Greater => high = mid,
+                Ordering::Equal => return Ok(mid),
+            }
+        }
+        Err(low)
+    }
+
+    pub fn contains(&self, word: &str) -> bool {
+        let mut buffer = [0; MAX_LETTER_SIZE * MAX_WORD_LENGTH];
+        self.pack(word, &mut buffer)
+            .is_some_and(|word| self.search(word).is_ok())
+    }
+
+    /// Returns `false` if the word was already in the list.
+    pub fn insert(&mut self, word: &str) -> bool {
+        let widest = word.chars().map(letter_size).max().unwrap_or(1);
+        if widest > self.letter_size {
+            self.widen(widest);
+        }
+        let mut buffer = [0; MAX_LETTER_SIZE * MAX_WORD_LENGTH];
+        let word = self.pack(word, &mut buffer).expect("Word of a wrong length");
+        match self.search(word) {
+            Ok(_) => false,
+            Err(index) => {
+                let at = index * self.word_size();
+                self.bytes.splice(at..at, word.iter().copied());
+                true
+            }
+        }
+    }
+
+    /// Returns `false` if the word wasn't in the list.
+    pub fn remove(&mut self, word: &str) -> bool {
+        let mut buffer = [0; MAX_LETTER_SIZE * MAX_WORD_LENGTH];
+        let Some(word) = self.pack(word, &mut buffer) else {
+            return false;
+        };
+        match self.search(word) {
+            Ok(index) => {
+                let at = index * self.word_size();
+                self.bytes.drain(at..at + self.word_size());
+                true
+            }
+            Err(_) => false,
+        }
+    }
+}
+
+impl WordList for PackedWords {
+    fn word_count(&self) -> usize {
+        self.len()
+    }
+
+    fn letters(&self, index: usize) -> Letters {
+        let mut letters = ['\0'; MAX_WORD_LENGTH];
+        let letter_size = self.letter_size as usize;
+        for (slot, c) in letters.iter_mut().zip(self.packed(index).chunks(letter_size)) {
+            let code = c.iter().fold(0, |code, byte| (code << 8) | *byte as u32);
+            *slot = char::from_u32(code).unwrap_or_default();
+        }
+        Letters {
+            letters,
+            length: self.word_length,
+        }
+    }
+}
+
+/// Blake2b-256 of the encoded, sorted word list of a sealed dictionary.
+pub fn dictionary_hash(words: &[String]) -> [u8; 32] {
+    Blake2b::<U32>::digest(words.encode()).into()
+}
+
+/// Blake2b-256 of the secret word followed by the salt, published when a game starts.
+pub fn word_commitment(word: &str, salt: &[u8; 32]) -> [u8; 32] {
+    Blake2b::<U32>::new()
+        .chain_update(word.as_bytes())
+        .chain_update(salt)
+        .finalize()
+        .into()
+}
+
+/// Checks off-chain that the word revealed at the end of a game is the one committed to
+/// at its start.
+pub fn verify_reveal(commitment: &[u8; 32], word: &str, salt: &[u8; 32]) -> bool {
+    word_commitment(word, salt) == *commitment
 }
 
 #[derive(Debug, Clone, Encode, Decode, TypeInfo)]
 pub enum Action {
-    StartGame { user: ActorId },
+    StartGame {
+        user: ActorId,
+        language: String,
+        length: u8,
+        /// Revealed hints have to be used in every later guess.
+        hard_mode: bool,
+        mode: GameMode,
+        /// Hidden words guessed at once, more than one only in classic games.
+        boards: u8,
+        /// Picks only words of the category in the sealed dictionary. Not with the daily word,
+        /// which is the same for everyone.
+        category: Option<String>,
+        /// Abandons an unfinished game instead of replying with [`Event::GameInProgress`].
+        force: bool,
+    },
     CheckWord { user: ActorId, word: String },
+    // Owner only, edits the draft that the next sealed dictionary of the language is built from
+    AddWords { language: String, words: Vec<String> },
+    RemoveWords { language: String, words: Vec<String> },
+    SealDictionary { language: String },
+    // Owner only, tags words of the draft. Words added to a category join the draft too,
+    // and words removed from the draft leave every category
+    CreateCategory { language: String, category: String },
+    RemoveCategory { language: String, category: String },
+    AddToCategory { language: String, category: String, words: Vec<String> },
+    RemoveFromCategory { language: String, category: String, words: Vec<String> },
+    // Owner only, guesses that are accepted but never picked as answers
+    AddAllowedGuesses { language: String, words: Vec<String> },
+    RemoveAllowedGuesses { language: String, words: Vec<String> },
+    /// Players who solved the daily word of the epoch, the fewest tries first.
+    DailyLeaderboard { epoch: u32 },
+    // Owner only, programs allowed to play on behalf of any user
+    AddTrustedCaller { program: ActorId },
+    RemoveTrustedCaller { program: ActorId },
+    /// Trusted callers only, e.g. a session that timed out.
+    EndGame { user: ActorId, result: GameResult },
+    /// Gives up the game, it counts as lost.
+    Abandon { user: ActorId },
+    /// Suggests a next guess for the first unsolved board, up to [`MAX_HINTS`] per game.
+    /// It doesn't count as a try, but the win is counted as assisted.
+    Hint { user: ActorId },
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Encode, Decode, TypeInfo)]
+pub enum GameMode {
+    /// A random word the player hasn't had yet.
+    #[default]
+    Classic,
+    /// The word of the day, shared by all players and playable once per epoch.
+    Daily,
+    /// No word is picked up front, every guess keeps the most candidates possible.
+    Absurdle,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode, TypeInfo)]
+pub enum GameResult {
+    Won,
+    Lost,
 }
 
 #[derive(Debug, Clone, Encode, Decode, TypeInfo)]
 pub enum Event {
     GameStarted {
         user: ActorId,
+        /// [`word_commitment`] of the secret word of every board, opened by
+        /// [`Event::GameRevealed`]. Empty in Absurdle mode, which has no word to commit to.
+        commitments: Vec<[u8; 32]>,
     },
     WordChecked {
         user: ActorId,
-        correct_position: Vec<u8>,
-        contained_in_word: Vec<u8>,
+        feedback: Feedback,
+    },
+    DictionaryUpdated {
+        draft_size: u32,
+    },
+    DictionarySealed {
+        language: String,
+        version: u32,
+        hash: [u8; 32],
+        size: u32,
+    },
+    /// The guess is not a known word, it doesn't count as a try.
+    InvalidWord {
+        user: ActorId,
+        word: String,
+    },
+    AllowedGuessesUpdated {
+        size: u32,
+    },
+    /// The hard mode guess ignores a revealed hint, it doesn't count as a try.
+    HardModeViolation {
+        user: ActorId,
+        violation: HardModeViolation,
+    },
+    DailyLeaderboard {
+        epoch: u32,
+        results: Vec<DailyResult>,
+    },
+    /// Sent to the player when the game is over, see [`verify_reveal`]. In Absurdle mode
+    /// the word is one of those still possible and the salt is zero.
+    GameRevealed {
+        user: ActorId,
+        board: u8,
+        word: String,
+        salt: [u8; 32],
+    },
+    TrustedCallersUpdated {
+        count: u32,
+    },
+    /// The game is over and its memory is freed, the word is revealed to the player.
+    GameEnded {
+        user: ActorId,
+        result: GameResult,
+    },
+    /// `StartGame` without `force` while the user still has an unfinished game.
+    GameInProgress {
+        user: ActorId,
+    },
+    /// [`Event::WordChecked`] of a multi-board game, with feedback for every unsolved board.
+    BoardsChecked {
+        user: ActorId,
+        feedback: Vec<BoardFeedback>,
     },
+    /// Words of the category in the draft, `None` once the category is removed.
+    CategoryUpdated {
+        language: String,
+        category: String,
+        size: Option<u32>,
+    },
+    /// A word consistent with all feedback of the board so far.
+    Hint {
+        user: ActorId,
+        board: u8,
+        word: String,
+        hints_left: u32,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode, TypeInfo)]
+pub struct BoardFeedback {
+    pub board: u8,
+    pub feedback: Feedback,
+}
+
+/// A running game as anyone can see it, without the secret word.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, TypeInfo)]
+pub struct GameInfo {
+    pub language: String,
+    pub length: u8,
+    pub category: Option<String>,
+    pub dictionary_version: u32,
+    pub mode: GameMode,
+    pub hard_mode: bool,
+    /// Epoch of the daily word, `None` in other modes.
+    pub daily_epoch: Option<u32>,
+    pub tries: u32,
+    pub hints: u32,
+    pub boards: u8,
+    pub solved_boards: u8,
+    pub commitments: Vec<[u8; 32]>,
+}
+
+/// The sealed dictionary new games of a language are played with.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, TypeInfo)]
+pub struct DictionaryInfo {
+    pub version: u32,
+    pub size: u32,
+    pub hash: [u8; 32],
+    /// Categories of the dictionary with the number of their words.
+    pub categories: Vec<(String, u32)>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Encode, Decode, TypeInfo)]
+pub struct UserStats {
+    pub games_played: u32,
+    pub wins: u32,
+    pub losses: u32,
+    /// Wins with at least one hint, they are counted in `wins` too.
+    pub assisted_wins: u32,
+}
+
+#[derive(Debug, Clone, Encode, Decode, TypeInfo)]
+pub enum StateQuery {
+    ActiveGames,
+    Dictionary { language: String },
+    Game(ActorId),
+    Stats(ActorId),
+    /// Size of the program memory in 64 KiB pages, for the benches.
+    MemoryPages,
+}
+
+#[derive(Debug, Clone, Encode, Decode, TypeInfo)]
+pub enum StateReply {
+    ActiveGames(u32),
+    Dictionary(Option<DictionaryInfo>),
+    Game(Option<GameInfo>),
+    Stats(UserStats),
+    MemoryPages(u32),
+}
+
+impl Event {
+    /// The player the event is about, if any.
+    pub fn user(&self) -> Option<&ActorId> {
+        match self {
+            Event::GameStarted { user, .. }
+            | Event::WordChecked { user, .. }
+            | Event::InvalidWord { user, .. }
+            | Event::HardModeViolation { user, .. }
+            | Event::GameRevealed { user, .. }
+            | Event::GameEnded { user, .. }
+            | Event::GameInProgress { user }
+            | Event::BoardsChecked { user, .. }
+            | Event::Hint { user, .. } => Some(user),
+            _ => None,
+        }
+    }
 }
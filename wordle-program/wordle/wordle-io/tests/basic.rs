@@ -0,0 +1,294 @@
+#[cfg(test)]
+mod tests {
+    use wordle_io::*;
+
+    // G = correct position, Y = present elsewhere, - = absent
+    fn pattern(answer: &str, guess: &str) -> String {
+        check_word(answer, guess)
+            .results()
+            .into_iter()
+            .map(|result| match result {
+                LetterResult::Correct => 'G',
+                LetterResult::Present => 'Y',
+                LetterResult::Absent => '-',
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_check_word_vectors() {
+        let vectors = [
+            ("house", "house", "GGGGG"),
+            ("house", "geese", "---GG"),
+            ("house", "hello", "GY--Y"),
+            ("house", "mouse", "-GGGG"),
+            ("house", "eerie", "----G"),
+            ("house", "sheep", "YYY--"),
+            ("abbey", "babes", "YYGG-"),
+            ("abbey", "kebab", "-YGYY"),
+            ("abbey", "bobby", "Y-G-G"),
+            ("abbey", "abyss", "GGY--"),
+            ("speed", "erase", "Y--YY"),
+            ("speed", "eerie", "YY---"),
+            ("speed", "deeds", "YYG-Y"),
+            ("crane", "nacre", "YYYYG"),
+            ("crane", "react", "YYGY-"),
+            ("crane", "zzzzz", "-----"),
+            ("array", "rarer", "YYG--"),
+            ("array", "radar", "YY-GY"),
+            ("array", "yarra", "YYGYY"),
+            ("llama", "lolly", "G-Y--"),
+            ("llama", "alarm", "YGG-Y"),
+            ("eerie", "sheen", "--YY-"),
+            ("eerie", "eeeee", "GG--G"),
+            ("mamma", "mamma", "GGGGG"),
+            ("mamma", "ammam", "YYGYY"),
+            ("bobby", "abbey", "-YG-G"),
+            ("geese", "eerie", "YG--G"),
+            ("geese", "house", "---GG"),
+            ("otter", "tatty", "Y-G--"),
+            ("otter", "rotor", "-YG-G"),
+            ("skill", "kills", "YYYGY"),
+            ("skill", "lilts", "YYY-Y"),
+            ("vivid", "ivied", "YYY-G"),
+            ("cocoa", "occur", "YYG--"),
+            ("aback", "quack", "--GGG"),
+            // Positions are counted in letters, not bytes
+            ("книга", "кинза", "GYY-G"),
+            ("мышка", "камыш", "YYYYY"),
+            ("grüße", "größe", "GG-GG"),
+            ("straße", "süßest", "G-YY-Y"),
+        ];
+
+        for (answer, guess, expected) in vectors {
+            assert_eq!(pattern(answer, guess), expected, "{} against {}", guess, answer);
+        }
+    }
+
+    #[test]
+    fn test_feedback_packing() {
+        use LetterResult::*;
+
+        let results = [Correct, Absent, Present, Present, Correct];
+        let feedback = Feedback::new(&results);
+        assert_eq!(feedback.len(), 5);
+        assert_eq!(feedback.packed, 0b10_01_01_00_10);
+        assert_eq!(feedback.results(), results);
+        assert!(!feedback.is_solved());
+
+        assert!(Feedback::new(&[Correct; 5]).is_solved());
+        assert!(!Feedback::default().is_solved());
+
+        // A full-length feedback still fits the packed integer
+        let feedback = Feedback::new(&[Correct; MAX_WORD_LENGTH]);
+        assert_eq!(feedback.packed, u32::MAX / 3 * 2);
+        assert!(feedback.is_solved());
+    }
+
+    #[test]
+    fn test_packed_words() {
+        let mut words = PackedWords::new(5);
+        for word in ["mouse", "house", "horse", "house"] {
+            words.insert(word);
+        }
+        assert_eq!(words.len(), 3);
+        assert_eq!(words.get(0), "horse");
+        assert_eq!(words.get(2), "mouse");
+        assert!(words.contains("house"));
+        assert!(!words.contains("hous"));
+        assert!(!words.contains("houses"));
+        assert!(!words.contains("louse"));
+        // ASCII letters take a byte each
+        assert_eq!(words.packed_size(), 15);
+
+        assert!(words.remove("house"));
+        assert!(!words.remove("house"));
+        assert!(!words.contains("house"));
+        assert_eq!(words.len(), 2);
+
+        // Every letter takes the same room, as much as the widest letter of the list needs
+        let mut words = PackedWords::new(5);
+        for word in ["grüße", "apfel"] {
+            words.insert(word);
+        }
+        assert_eq!(words.packed_size(), 10);
+        assert!(!words.contains("мышка"));
+        words.insert("мышка");
+        assert_eq!(words.packed_size(), 30);
+        assert_eq!(words.get(0), "apfel");
+        assert_eq!(words.get(2), "мышка");
+        assert!(words.contains("grüße"));
+        assert!(!words.contains("grüß"));
+
+        // Packed words are scored as fixed arrays of letters
+        assert_eq!(words.letters(1), Letters::new("grüße"));
+        assert_eq!(words.letters(2).as_slice(), ['м', 'ы', 'ш', 'к', 'а']);
+        assert_eq!(
+            check_letters(&words.letters(1), &Letters::new("größe")),
+            check_word("grüße", "größe")
+        );
+    }
+
+    #[test]
+    fn test_normalize_word() {
+        assert_eq!(normalize_word("HoUsE"), "house");
+        assert_eq!(normalize_word("КНИГА"), "книга");
+        // Decomposed letters are composed
+        assert_eq!(normalize_word("GRU\u{308}SSE"), "grüsse");
+        assert_eq!(normalize_word("gru\u{308}ße"), "grüße");
+    }
+
+    #[test]
+    fn test_hard_mode_constraints() {
+        let mut constraints = HardModeConstraints::default();
+        assert_eq!(constraints.check("hello"), Ok(()));
+
+        // Three "e" are revealed, two of them in place
+        constraints.update("geese", &check_word("eerie", "geese"));
+        assert_eq!(constraints.check("eerie"), Ok(()));
+        assert_eq!(
+            constraints.check("ezzze"),
+            Err(HardModeViolation::CorrectLetterMoved {
+                position: 1,
+                letter: "e".to_string(),
+            })
+        );
+        assert_eq!(
+            constraints.check("zezze"),
+            Err(HardModeViolation::MissingLetter {
+                letter: "e".to_string(),
+                count: 3,
+            })
+        );
+
+        // Later guesses only tighten the constraints
+        constraints.update("there", &check_word("eerie", "there"));
+        assert_eq!(constraints.required.get(&'e'), Some(&3));
+        assert_eq!(constraints.required.get(&'r'), Some(&1));
+    }
+
+    #[test]
+    fn test_index_set() {
+        let mut set = IndexSet::default();
+        assert!(set.is_empty());
+        assert_eq!(set.nth_absent(3), 3);
+
+        for index in [0, 2, 63, 64, 200] {
+            assert!(set.insert(index));
+        }
+        assert!(!set.insert(64));
+        assert_eq!(set.len(), 5);
+        assert!(set.contains(63));
+        assert!(!set.contains(65));
+        assert!(!set.contains(1000));
+
+        // Absent indexes are 1, 3, 4, ..., 62, 65, ...
+        assert_eq!(set.nth_absent(0), 1);
+        assert_eq!(set.nth_absent(1), 3);
+        assert_eq!(set.nth_absent(60), 62);
+        assert_eq!(set.nth_absent(61), 65);
+        assert_eq!(set.nth_absent(196), 201);
+
+        assert!(set.remove(64));
+        assert!(!set.remove(64));
+        assert!(!set.remove(1000));
+        assert_eq!(set.nth_absent(61), 64);
+
+        set.clear();
+        assert!(set.is_empty());
+    }
+
+    #[test]
+    fn test_narrow_candidates() {
+        let words: Vec<String> = ["horse", "house", "louse", "mouse", "rouse"]
+            .iter()
+            .map(|word| word.to_string())
+            .collect();
+        let mut candidates = IndexSet::with_all(words.len());
+        assert_eq!(candidates.iter().collect::<Vec<_>>(), [0, 1, 2, 3, 4]);
+
+        // The largest group is kept
+        let feedback = narrow_candidates(&words, &mut candidates, "house");
+        assert_eq!(feedback, check_word("mouse", "house"));
+        assert_eq!(candidates.iter().collect::<Vec<_>>(), [2, 3, 4]);
+        narrow_candidates(&words, &mut candidates, "mouse");
+        assert_eq!(candidates.iter().collect::<Vec<_>>(), [2, 4]);
+
+        // On a tie the group with fewer hints is kept
+        let feedback = narrow_candidates(&words, &mut candidates, "rouse");
+        assert!(!feedback.is_solved());
+        assert_eq!(candidates.iter().collect::<Vec<_>>(), [2]);
+        assert!(narrow_candidates(&words, &mut candidates, "louse").is_solved());
+
+        // Hints are counted, a single late hint beats two early ones
+        let words: Vec<String> = ["ffabg", "fefgh"].iter().map(|word| word.to_string()).collect();
+        let mut candidates = IndexSet::with_all(words.len());
+        let feedback = narrow_candidates(&words, &mut candidates, "abcde");
+        assert_eq!(feedback, check_word("fefgh", "abcde"));
+        assert_eq!(candidates.iter().collect::<Vec<_>>(), [1]);
+
+        let all = IndexSet::with_all(130);
+        assert_eq!(all.len(), 130);
+        assert!(all.contains(129));
+        assert!(!all.contains(130));
+    }
+
+    #[test]
+    fn test_solver() {
+        let words: Vec<String> = ["horse", "house", "louse", "mouse", "rouse"]
+            .iter()
+            .map(|word| word.to_string())
+            .collect();
+        let mut candidates = IndexSet::with_all(words.len());
+
+        // "horse" tells every other word apart but "louse" and "mouse"
+        assert_eq!(suggest_guess(&words, &candidates), Some(0));
+        filter_candidates(&words, &mut candidates, "horse", &check_word("mouse", "horse"));
+        assert_eq!(candidates.iter().collect::<Vec<_>>(), [2, 3]);
+        filter_candidates(&words, &mut candidates, "louse", &check_word("mouse", "louse"));
+        assert_eq!(candidates.iter().collect::<Vec<_>>(), [3]);
+        assert_eq!(suggest_guess(&words, &candidates), Some(3));
+
+        assert_eq!(suggest_guess(&words, &IndexSet::default()), None);
+    }
+
+    // Counts the words the solver reads
+    struct CountingWords {
+        words: PackedWords,
+        reads: std::cell::Cell<usize>,
+    }
+
+    impl WordList for CountingWords {
+        fn word_count(&self) -> usize {
+            self.words.word_count()
+        }
+
+        fn letters(&self, index: usize) -> Letters {
+            self.reads.set(self.reads.get() + 1);
+            self.words.letters(index)
+        }
+    }
+
+    #[test]
+    fn test_solver_on_large_bank() {
+        // 26^3 words "aaaab" to "zzzab", about the size of a full five-letter bank
+        let mut words = PackedWords::new(5);
+        for a in 'a'..='z' {
+            for b in 'a'..='z' {
+                for c in 'a'..='z' {
+                    words.insert(&format!("{a}{b}{c}ab"));
+                }
+            }
+        }
+        let words = CountingWords {
+            words,
+            reads: Default::default(),
+        };
+        let candidates = IndexSet::with_all(words.word_count());
+
+        // Only a sample of the candidates is read and scored
+        let index = suggest_guess(&words, &candidates).unwrap();
+        assert!(candidates.contains(index));
+        assert!(words.reads.get() <= HINT_GUESSES + HINT_ANSWERS);
+    }
+}